@@ -0,0 +1,70 @@
+use bytes::BytesMut;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use simple_redis::{
+    network::RespFrameCodec,
+    resp::{BulkString, RespArray, RespEncode, RespFrame},
+};
+use tokio_util::codec::Decoder;
+
+// socket reads are usually around this size
+const READ_SIZE: usize = 16 * 1024;
+
+// An MSET-like request with `size` bytes worth of 64 byte bulk strings.
+fn request(size: usize) -> Vec<u8> {
+    let frames: Vec<RespFrame> = (0..size / 64)
+        .map(|i| BulkString::new(format!("{:064}", i)).into())
+        .collect();
+    RespArray::new(frames).encode()
+}
+
+fn decode_chunked(data: &[u8]) -> RespFrame {
+    let mut codec = RespFrameCodec::default();
+    let mut buf = BytesMut::with_capacity(READ_SIZE);
+    for chunk in data.chunks(READ_SIZE) {
+        buf.extend_from_slice(chunk);
+        if let Some(frame) = codec.decode(&mut buf).unwrap() {
+            return frame;
+        }
+    }
+    panic!("incomplete frame");
+}
+
+fn bench_chunked(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_chunked");
+    group.sample_size(10);
+    for mb in [1, 4, 16] {
+        let data = request(mb * 1024 * 1024);
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{mb}MB")),
+            &data,
+            |b, data| b.iter(|| decode_chunked(black_box(data))),
+        );
+    }
+    group.finish();
+}
+
+fn bench_whole(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_whole");
+    group.sample_size(10);
+    for mb in [1, 4, 16] {
+        let data = request(mb * 1024 * 1024);
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{mb}MB")),
+            &data,
+            |b, data| {
+                b.iter(|| {
+                    let mut buf = BytesMut::from(&data[..]);
+                    RespFrameCodec::default()
+                        .decode(black_box(&mut buf))
+                        .unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_chunked, bench_whole);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+#![no_main]
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use simple_redis::resp::{RespDecode, RespFrame, RespParser};
+
+// Decoding must never panic, whatever the peer sends.
+fuzz_target!(|data: &[u8]| {
+    let _ = RespFrame::expect_length(data);
+    let _ = RespFrame::decode(&mut BytesMut::from(data));
+
+    // feed the same input to the incremental parser in two reads
+    let mut parser = RespParser::new();
+    let (head, tail) = data.split_at(data.len() / 2);
+    let mut buf = BytesMut::from(head);
+    while let Ok(Some(_)) = parser.parse(&mut buf) {}
+    buf.extend_from_slice(tail);
+    while let Ok(Some(_)) = parser.parse(&mut buf) {}
+});
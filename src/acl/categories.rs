@@ -0,0 +1,151 @@
+// ACL categories, as listed by ACL CAT.
+pub const CATEGORIES: &[&str] = &[
+    "keyspace",
+    "read",
+    "write",
+    "set",
+    "sortedset",
+    "list",
+    "hash",
+    "string",
+    "bitmap",
+    "hyperloglog",
+    "geo",
+    "stream",
+    "pubsub",
+    "admin",
+    "fast",
+    "slow",
+    "blocking",
+    "dangerous",
+    "connection",
+    "transaction",
+    "scripting",
+];
+
+// A command and the categories it belongs to. Container commands such as
+// CONFIG list their subcommands, which may be in other categories than the
+// container and can be allowed or denied one by one with `+config|get`.
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub categories: &'static [&'static str],
+    pub subcommands: &'static [SubcommandSpec],
+}
+
+pub struct SubcommandSpec {
+    pub name: &'static str,
+    pub categories: &'static [&'static str],
+}
+
+const fn command(name: &'static str, categories: &'static [&'static str]) -> CommandSpec {
+    CommandSpec {
+        name,
+        categories,
+        subcommands: &[],
+    }
+}
+
+const fn sub(name: &'static str, categories: &'static [&'static str]) -> SubcommandSpec {
+    SubcommandSpec { name, categories }
+}
+
+const ADMIN: &[&str] = &["admin", "slow", "dangerous"];
+const CLIENT: &[&str] = &["slow", "connection"];
+const PUBSUB: &[&str] = &["pubsub", "slow"];
+
+pub const COMMANDS: &[CommandSpec] = &[
+    command("get", &["read", "string", "fast"]),
+    command("set", &["write", "string", "slow"]),
+    command("subscribe", PUBSUB),
+    command("unsubscribe", PUBSUB),
+    command("psubscribe", PUBSUB),
+    command("punsubscribe", PUBSUB),
+    command("publish", &["pubsub", "fast"]),
+    command("ssubscribe", PUBSUB),
+    command("sunsubscribe", PUBSUB),
+    command("spublish", &["pubsub", "fast"]),
+    CommandSpec {
+        name: "pubsub",
+        categories: PUBSUB,
+        subcommands: &[
+            sub("channels", PUBSUB),
+            sub("numsub", PUBSUB),
+            sub("numpat", PUBSUB),
+            sub("shardchannels", PUBSUB),
+            sub("shardnumsub", PUBSUB),
+        ],
+    },
+    command("info", &["slow", "dangerous"]),
+    command("shutdown", ADMIN),
+    CommandSpec {
+        name: "client",
+        categories: CLIENT,
+        subcommands: &[
+            sub("id", CLIENT),
+            sub("tracking", CLIENT),
+            sub("caching", CLIENT),
+            sub("getredir", CLIENT),
+            sub("setname", CLIENT),
+            sub("getname", CLIENT),
+        ],
+    },
+    CommandSpec {
+        name: "config",
+        categories: ADMIN,
+        subcommands: &[
+            sub("get", ADMIN),
+            sub("set", ADMIN),
+            sub("rewrite", ADMIN),
+            sub("resetstat", ADMIN),
+        ],
+    },
+    command("hello", &["fast", "connection"]),
+    command("auth", &["fast", "connection"]),
+    CommandSpec {
+        name: "acl",
+        categories: &["slow"],
+        subcommands: &[
+            sub("cat", &["slow"]),
+            sub("deluser", ADMIN),
+            sub("getuser", ADMIN),
+            sub("list", ADMIN),
+            sub("load", ADMIN),
+            sub("log", ADMIN),
+            sub("save", ADMIN),
+            sub("setuser", ADMIN),
+            sub("whoami", &["slow"]),
+        ],
+    },
+];
+
+pub fn find_command(name: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+}
+
+impl CommandSpec {
+    pub fn find_subcommand(&self, name: &str) -> Option<&'static SubcommandSpec> {
+        self.subcommands
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+}
+
+// ACL CAT <category>: the commands in the category, subcommands are named
+// `container|subcommand`.
+pub fn commands_in_category(category: &str) -> Vec<String> {
+    let mut commands = vec![];
+    for command in COMMANDS {
+        if command.subcommands.is_empty() {
+            if command.categories.contains(&category) {
+                commands.push(command.name.to_string());
+            }
+            continue;
+        }
+        for sub in command.subcommands {
+            if sub.categories.contains(&category) {
+                commands.push(format!("{}|{}", command.name, sub.name));
+            }
+        }
+    }
+    commands
+}
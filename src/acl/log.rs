@@ -0,0 +1,144 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// Attempts closer than this are counted in the same entry.
+const GROUP_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DenyReason {
+    Auth,
+    Command,
+    Key,
+    Channel,
+}
+
+impl DenyReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DenyReason::Auth => "auth",
+            DenyReason::Command => "command",
+            DenyReason::Key => "key",
+            DenyReason::Channel => "channel",
+        }
+    }
+}
+
+// A denied command or failed authentication, as listed by ACL LOG.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AclLogEntry {
+    pub count: u64,
+    pub reason: DenyReason,
+    // the command, key or channel that was denied, or AUTH
+    pub object: String,
+    pub username: String,
+    pub client_info: String,
+    pub entry_id: u64,
+    pub created: SystemTime,
+    pub updated: SystemTime,
+}
+
+impl AclLogEntry {
+    pub fn age(&self) -> Duration {
+        self.created.elapsed().unwrap_or_default()
+    }
+}
+
+// The most recent entries first, at most `acllog-max-len` of them.
+#[derive(Debug, Default)]
+pub struct AclLog {
+    entries: VecDeque<AclLogEntry>,
+    next_id: u64,
+}
+
+impl AclLog {
+    pub fn add(
+        &mut self,
+        reason: DenyReason,
+        object: String,
+        username: String,
+        client_info: String,
+        max_len: usize,
+    ) {
+        let now = SystemTime::now();
+        // the same denial repeated within a short time is counted once
+        let similar = self.entries.iter().position(|e| {
+            e.reason == reason
+                && e.object == object
+                && e.username == username
+                && now.duration_since(e.updated).unwrap_or_default() < GROUP_WINDOW
+        });
+        let entry = match similar.and_then(|i| self.entries.remove(i)) {
+            Some(entry) => AclLogEntry {
+                count: entry.count + 1,
+                client_info,
+                updated: now,
+                ..entry
+            },
+            None => {
+                self.next_id += 1;
+                AclLogEntry {
+                    count: 1,
+                    reason,
+                    object,
+                    username,
+                    client_info,
+                    entry_id: self.next_id - 1,
+                    created: now,
+                    updated: now,
+                }
+            }
+        };
+        self.entries.push_front(entry);
+        self.entries.truncate(max_len);
+    }
+
+    pub fn entries(&self, count: usize) -> Vec<AclLogEntry> {
+        self.entries.iter().take(count).cloned().collect()
+    }
+
+    pub fn reset(&mut self) {
+        self.entries.clear();
+    }
+}
+
+pub fn timestamp_ms(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acl_log() {
+        let mut log = AclLog::default();
+        let mut add = |reason, object: &str, username: &str, max_len| {
+            log.add(
+                reason,
+                object.to_string(),
+                username.to_string(),
+                "id=1".to_string(),
+                max_len,
+            )
+        };
+        add(DenyReason::Command, "get", "app", 10);
+        add(DenyReason::Key, "secret", "app", 10);
+        add(DenyReason::Command, "get", "app", 10);
+        add(DenyReason::Auth, "AUTH", "admin", 2);
+
+        let entries = log.entries(10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].reason, DenyReason::Auth);
+        assert_eq!(entries[0].entry_id, 2);
+        assert_eq!((entries[1].object.as_str(), entries[1].count), ("get", 2));
+        assert_eq!(entries[1].entry_id, 0);
+        assert_eq!(log.entries(1).len(), 1);
+
+        log.reset();
+        assert!(log.entries(10).is_empty());
+    }
+}
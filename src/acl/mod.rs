@@ -0,0 +1,352 @@
+mod categories;
+mod log;
+mod user;
+
+pub use categories::{commands_in_category, find_command, CATEGORIES};
+pub use log::{timestamp_ms, AclLogEntry, DenyReason};
+pub use user::{KeyAccess, User};
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, RwLock},
+};
+
+use bytes::Bytes;
+use thiserror::Error;
+
+use crate::{command::Command, resp::split_args};
+
+use log::AclLog;
+
+#[derive(Debug, Error)]
+pub enum AclError {
+    #[error("Error in ACL SETUSER modifier '{rule}': {message}")]
+    InvalidRule { rule: String, message: String },
+    #[error("Usernames can't contain spaces or null characters")]
+    InvalidUsername,
+    #[error("The 'default' user cannot be removed")]
+    DefaultUser,
+    #[error("This Redis instance is not configured to use an ACL file. You may want to specify users via the ACL SETUSER command and then issue a CONFIG REWRITE (assuming you have a Redis configuration file set) in order to store users in the Redis configuration.")]
+    NoAclFile,
+    #[error("{location}: {message}")]
+    Invalid { location: String, message: String },
+    #[error("can't access ACL file '{}': {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+// Why a command was refused, see `Acl::check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Denied {
+    // the user the client authenticated as was deleted
+    NoUser,
+    Command,
+    Key(Bytes),
+    Channel(Bytes),
+}
+
+// Users and what they may do. Clients start as `default` and switch user
+// with AUTH. Permissions are looked up for every command, so changes apply
+// to connected clients right away.
+#[derive(Debug)]
+pub struct Acl {
+    users: RwLock<BTreeMap<String, User>>,
+    log: Mutex<AclLog>,
+}
+
+impl Default for Acl {
+    fn default() -> Self {
+        Self {
+            users: RwLock::new(default_users()),
+            log: Mutex::default(),
+        }
+    }
+}
+
+fn default_users() -> BTreeMap<String, User> {
+    BTreeMap::from([("default".to_string(), User::new_default())])
+}
+
+impl Acl {
+    // Whether new clients are authenticated without AUTH.
+    pub fn default_user_needs_no_auth(&self) -> bool {
+        let users = self.users.read().unwrap();
+        users
+            .get("default")
+            .is_some_and(|u| u.is_enabled() && u.is_nopass())
+    }
+
+    // `requirepass` is the password of the default user.
+    pub fn set_requirepass(&self, password: Option<&str>) {
+        let mut users = self.users.write().unwrap();
+        let user = users
+            .entry("default".to_string())
+            .or_insert_with(User::new_default);
+        let rules = match password {
+            Some(password) => vec!["resetpass".to_string(), format!(">{}", password)],
+            None => vec!["nopass".to_string()],
+        };
+        for rule in rules {
+            user.apply(&rule).expect("valid rule");
+        }
+    }
+
+    // Disabled users can't authenticate, whatever their password.
+    pub fn authenticate(&self, username: &[u8], password: &[u8]) -> bool {
+        let users = self.users.read().unwrap();
+        std::str::from_utf8(username)
+            .ok()
+            .and_then(|name| users.get(name))
+            .is_some_and(|u| u.is_enabled() && u.check_password(password))
+    }
+
+    // Check the command and the keys and channels it accesses against the
+    // permissions of `username`.
+    pub fn check(&self, username: &str, cmd: &Command) -> Result<(), Denied> {
+        let users = self.users.read().unwrap();
+        let user = users.get(username).ok_or(Denied::NoUser)?;
+        let (name, sub) = cmd.acl_name();
+        if !user.can_run(name, sub) {
+            return Err(Denied::Command);
+        }
+        for (key, access) in cmd.acl_keys() {
+            if !user.can_access_key(key, access) {
+                return Err(Denied::Key(key.clone()));
+            }
+        }
+        for (channel, is_pattern) in cmd.acl_channels() {
+            if !user.can_access_channel(channel, is_pattern) {
+                return Err(Denied::Channel(channel.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn user(&self, name: &str) -> Option<User> {
+        self.users.read().unwrap().get(name).cloned()
+    }
+
+    // All users, sorted by name.
+    pub fn users(&self) -> Vec<User> {
+        self.users.read().unwrap().values().cloned().collect()
+    }
+
+    // ACL SETUSER: create the user if needed and apply the rules, all of
+    // them or none.
+    pub fn set_user(&self, name: &str, rules: &[String]) -> Result<(), AclError> {
+        if name.contains([' ', '\0']) {
+            return Err(AclError::InvalidUsername);
+        }
+        let mut users = self.users.write().unwrap();
+        let mut user = users.get(name).cloned().unwrap_or_else(|| User::new(name));
+        for rule in rules {
+            user.apply(rule).map_err(|message| AclError::InvalidRule {
+                rule: rule.clone(),
+                message,
+            })?;
+        }
+        users.insert(name.to_string(), user);
+        Ok(())
+    }
+
+    // ACL DELUSER: the number of users deleted.
+    pub fn delete_users(&self, names: &[String]) -> Result<usize, AclError> {
+        if names.iter().any(|name| name == "default") {
+            return Err(AclError::DefaultUser);
+        }
+        let mut users = self.users.write().unwrap();
+        Ok(names
+            .iter()
+            .filter(|name| users.remove(name.as_str()).is_some())
+            .count())
+    }
+
+    pub fn log_denied(
+        &self,
+        reason: DenyReason,
+        object: String,
+        username: String,
+        client_info: String,
+        max_len: usize,
+    ) {
+        self.log
+            .lock()
+            .unwrap()
+            .add(reason, object, username, client_info, max_len);
+    }
+
+    pub fn log_entries(&self, count: usize) -> Vec<AclLogEntry> {
+        self.log.lock().unwrap().entries(count)
+    }
+
+    pub fn reset_log(&self) {
+        self.log.lock().unwrap().reset();
+    }
+
+    // ACL LOAD: replace every user with those of the file. Nothing changes if
+    // any line is invalid. `default` keeps its default rules unless the file
+    // sets it.
+    pub fn load(&self, path: &Path) -> Result<(), AclError> {
+        let text = fs::read_to_string(path).map_err(|source| AclError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let users = parse_users(&text, &path.display().to_string())?;
+        *self.users.write().unwrap() = users;
+        Ok(())
+    }
+
+    // ACL SAVE: the new file replaces the old one at once, it is never left
+    // half written.
+    pub fn save(&self, path: &Path) -> Result<(), AclError> {
+        let io_error = |source| AclError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let text: String = self
+            .users()
+            .iter()
+            .map(|u| format!("user {} {}\n", u.name(), u.description()))
+            .collect();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(format!(".save-{}", std::process::id()));
+        fs::write(&tmp, text).map_err(io_error)?;
+        fs::rename(&tmp, path).map_err(io_error)
+    }
+}
+
+// One `user <name> [rule ...]` line per user, each starting from a user
+// without any permission.
+fn parse_users(text: &str, origin: &str) -> Result<BTreeMap<String, User>, AclError> {
+    let mut users = BTreeMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let invalid = |message: String| AclError::Invalid {
+            location: format!("{}:{}", origin, i + 1),
+            message,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let args = split_args(line.as_bytes())
+            .ok_or_else(|| invalid("unbalanced quotes".to_string()))?
+            .into_iter()
+            .map(String::from_utf8)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid("invalid UTF-8".to_string()))?;
+        let [keyword, name, rules @ ..] = args.as_slice() else {
+            return Err(invalid("should start with user keyword".to_string()));
+        };
+        if !keyword.eq_ignore_ascii_case("user") {
+            return Err(invalid("should start with user keyword".to_string()));
+        }
+        if users.contains_key(name) {
+            return Err(invalid(format!("duplicate user '{}' found", name)));
+        }
+        if name.contains([' ', '\0']) {
+            return Err(invalid(AclError::InvalidUsername.to_string()));
+        }
+        let mut user = User::new(name.as_str());
+        for rule in rules {
+            user.apply(rule)
+                .map_err(|message| invalid(format!("'{}': {}", rule, message)))?;
+        }
+        users.insert(name.clone(), user);
+    }
+    users
+        .entry("default".to_string())
+        .or_insert_with(User::new_default);
+    Ok(users)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_delete_users() -> anyhow::Result<()> {
+        let acl = Acl::default();
+        assert!(acl.default_user_needs_no_auth());
+        acl.set_requirepass(Some("pass"));
+        assert!(!acl.default_user_needs_no_auth());
+        assert!(acl.authenticate(b"default", b"pass"));
+        acl.set_requirepass(None);
+        assert!(acl.default_user_needs_no_auth());
+
+        acl.set_user("app", &["on".to_string(), ">secret".to_string()])?;
+        assert!(acl.authenticate(b"app", b"secret"));
+        assert!(!acl.authenticate(b"app", b"wrong"));
+        assert!(!acl.authenticate(b"nobody", b"secret"));
+
+        // rules are applied all or none
+        let err = acl
+            .set_user("app", &["off".to_string(), "+nosuch".to_string()])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error in ACL SETUSER modifier '+nosuch': Unknown command or category name in ACL"
+        );
+        assert!(acl.authenticate(b"app", b"secret"));
+        acl.set_user("app", &["off".to_string()])?;
+        assert!(!acl.authenticate(b"app", b"secret"));
+
+        assert!(matches!(
+            acl.delete_users(&["app".to_string(), "default".to_string()]),
+            Err(AclError::DefaultUser)
+        ));
+        assert_eq!(
+            acl.delete_users(&["app".to_string(), "nobody".to_string()])?,
+            1
+        );
+        assert_eq!(
+            acl.users().iter().map(|u| u.name()).collect::<Vec<_>>(),
+            vec!["default"]
+        );
+        assert!(matches!(
+            acl.set_user("a b", &[]),
+            Err(AclError::InvalidUsername)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_and_load() -> anyhow::Result<()> {
+        let acl = Acl::default();
+        acl.set_user(
+            "app",
+            &["on", ">secret", "~app:*", "&app.*", "+@read"].map(String::from),
+        )?;
+        acl.set_user("admin", &["on", "nopass", "+@all"].map(String::from))?;
+        let path =
+            std::env::temp_dir().join(format!("simple-redis-users-{}.acl", std::process::id()));
+        acl.save(&path)?;
+
+        let loaded = Acl::default();
+        let ret = loaded.load(&path);
+        let text = fs::read_to_string(&path);
+        fs::remove_file(&path)?;
+        ret?;
+        assert_eq!(loaded.users(), acl.users());
+        assert!(text?.starts_with("user admin on nopass resetchannels +@all\nuser app on #"));
+
+        let err = parse_users("user app on\nuser app off\n", "users.acl").unwrap_err();
+        assert_eq!(err.to_string(), "users.acl:2: duplicate user 'app' found");
+        let err = parse_users("\nuser app on +nosuch\n", "users.acl").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "users.acl:2: '+nosuch': Unknown command or category name in ACL"
+        );
+        let err = parse_users("app on\n", "users.acl").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "users.acl:1: should start with user keyword"
+        );
+
+        // default gets its default rules unless the file sets it
+        let users = parse_users("user app on\n", "users.acl")?;
+        assert_eq!(users.get("default"), Some(&User::new_default()));
+        let users = parse_users("user default off\n", "users.acl")?;
+        assert!(!users["default"].is_enabled());
+        Ok(())
+    }
+}
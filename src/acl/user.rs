@@ -0,0 +1,472 @@
+use std::fmt;
+
+use crate::{
+    auth::{digest_eq, hash_password},
+    glob::glob_match,
+};
+
+use super::categories::{find_command, CATEGORIES};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAccess {
+    Read,
+    Write,
+}
+
+// `~pattern`, `%R~pattern` or `%W~pattern`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyPattern {
+    pattern: String,
+    read: bool,
+    write: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CommandRule {
+    All,
+    Category(&'static str),
+    Command(&'static str),
+    Subcommand(&'static str, &'static str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    name: String,
+    enabled: bool,
+    // any password is accepted
+    nopass: bool,
+    // SHA-256 of the passwords, the passwords themselves are never stored
+    passwords: Vec<[u8; 32]>,
+    // `+`/`-` rules in the order they were given, the last one matching a
+    // command decides, nothing is allowed without any
+    commands: Vec<(bool, CommandRule)>,
+    keys: Vec<KeyPattern>,
+    channels: Vec<String>,
+}
+
+impl User {
+    // A user as ACL SETUSER creates it: disabled, without passwords and
+    // allowed nothing.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            enabled: false,
+            nopass: false,
+            passwords: vec![],
+            commands: vec![],
+            keys: vec![],
+            channels: vec![],
+        }
+    }
+
+    // The `default` user clients start as, allowed everything without a
+    // password until configured otherwise.
+    pub fn new_default() -> Self {
+        let mut user = Self::new("default");
+        for rule in ["on", "nopass", "~*", "&*", "+@all"] {
+            user.apply(rule).expect("valid rule");
+        }
+        user
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_nopass(&self) -> bool {
+        self.nopass
+    }
+
+    // Apply one ACL SETUSER rule, keywords are case insensitive.
+    pub fn apply(&mut self, rule: &str) -> Result<(), String> {
+        match rule.to_ascii_lowercase().as_str() {
+            "on" => self.enabled = true,
+            "off" => self.enabled = false,
+            "nopass" => {
+                self.nopass = true;
+                self.passwords.clear();
+            }
+            "resetpass" => {
+                self.nopass = false;
+                self.passwords.clear();
+            }
+            "allkeys" => return self.apply("~*"),
+            "resetkeys" => self.keys.clear(),
+            "allchannels" => return self.apply("&*"),
+            "resetchannels" => self.channels.clear(),
+            "allcommands" => return self.apply("+@all"),
+            "nocommands" => return self.apply("-@all"),
+            "reset" => {
+                for rule in ["resetpass", "resetkeys", "resetchannels", "off", "-@all"] {
+                    self.apply(rule)?;
+                }
+            }
+            _ => return self.apply_value(rule),
+        }
+        Ok(())
+    }
+
+    fn apply_value(&mut self, rule: &str) -> Result<(), String> {
+        let mut chars = rule.chars();
+        let (Some(op), value) = (chars.next(), chars.as_str()) else {
+            return Err(syntax_error());
+        };
+        match op {
+            '>' => self.add_password(hash_password(value.as_bytes())),
+            '#' => self.add_password(parse_hash(value)?),
+            '<' => self.remove_password(hash_password(value.as_bytes()))?,
+            '!' => self.remove_password(parse_hash(value)?)?,
+            '~' => self.add_key_pattern(value, true, true),
+            '%' => {
+                let (perms, pattern) = value.split_once('~').ok_or_else(syntax_error)?;
+                let read = perms.contains(['r', 'R']);
+                let write = perms.contains(['w', 'W']);
+                if perms.is_empty() || perms.contains(|c| !"rRwW".contains(c)) {
+                    return Err(syntax_error());
+                }
+                self.add_key_pattern(pattern, read, write);
+            }
+            '&' => {
+                if !self.channels.iter().any(|c| c == value) {
+                    self.channels.push(value.to_string());
+                }
+            }
+            '+' | '-' => {
+                let rule = parse_command_rule(value)?;
+                if rule == CommandRule::All {
+                    self.commands.clear();
+                    // no rules denies everything already
+                    if op == '-' {
+                        return Ok(());
+                    }
+                }
+                self.commands.push((op == '+', rule));
+            }
+            _ => return Err(syntax_error()),
+        }
+        Ok(())
+    }
+
+    fn add_password(&mut self, hash: [u8; 32]) {
+        self.nopass = false;
+        if !self.passwords.contains(&hash) {
+            self.passwords.push(hash);
+        }
+    }
+
+    fn remove_password(&mut self, hash: [u8; 32]) -> Result<(), String> {
+        let len = self.passwords.len();
+        self.passwords.retain(|p| *p != hash);
+        if self.passwords.len() == len {
+            return Err(
+                "The password you are trying to remove from the user does not exist".to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    fn add_key_pattern(&mut self, pattern: &str, read: bool, write: bool) {
+        let pattern = KeyPattern {
+            pattern: pattern.to_string(),
+            read,
+            write,
+        };
+        if !self.keys.contains(&pattern) {
+            self.keys.push(pattern);
+        }
+    }
+
+    // Every hash is compared, whether one matches doesn't show in the timing.
+    pub fn check_password(&self, password: &[u8]) -> bool {
+        let hash = hash_password(password);
+        self.passwords
+            .iter()
+            .fold(self.nopass, |valid, p| digest_eq(p, &hash) | valid)
+    }
+
+    // Whether the user may run `name`, or its subcommand `sub` if it is a
+    // container command such as CONFIG. Unknown commands are only allowed
+    // by `+@all`.
+    pub fn can_run(&self, name: &str, sub: Option<&str>) -> bool {
+        let command = find_command(name);
+        let subcommand = command.zip(sub).and_then(|(c, sub)| c.find_subcommand(sub));
+        let categories = match (command, subcommand) {
+            (_, Some(sub)) => sub.categories,
+            (Some(command), None) => command.categories,
+            (None, None) => &[],
+        };
+        let name = command.map(|c| c.name);
+
+        self.commands
+            .iter()
+            .rfind(|(_, rule)| match rule {
+                CommandRule::All => true,
+                CommandRule::Category(category) => categories.contains(category),
+                CommandRule::Command(command) => name == Some(*command),
+                CommandRule::Subcommand(command, sub) => {
+                    name == Some(*command) && subcommand.is_some_and(|s| s.name == *sub)
+                }
+            })
+            .is_some_and(|(allow, _)| *allow)
+    }
+
+    pub fn can_access_key(&self, key: &[u8], access: KeyAccess) -> bool {
+        self.keys.iter().any(|k| {
+            let allowed = match access {
+                KeyAccess::Read => k.read,
+                KeyAccess::Write => k.write,
+            };
+            allowed && glob_match(k.pattern.as_bytes(), key)
+        })
+    }
+
+    // Patterns given to PSUBSCRIBE must be allowed as they are, they are not
+    // matched against the allowed patterns. `&*` allows everything.
+    pub fn can_access_channel(&self, channel: &[u8], is_pattern: bool) -> bool {
+        self.channels.iter().any(|c| {
+            c == "*"
+                || if is_pattern {
+                    c.as_bytes() == channel
+                } else {
+                    glob_match(c.as_bytes(), channel)
+                }
+        })
+    }
+
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut flags = vec![if self.enabled { "on" } else { "off" }];
+        if self.nopass {
+            flags.push("nopass");
+        }
+        flags
+    }
+
+    pub fn password_hashes(&self) -> Vec<String> {
+        self.passwords.iter().map(|p| hex(p)).collect()
+    }
+
+    pub fn commands_description(&self) -> String {
+        let mut rules = vec![];
+        if !matches!(self.commands.first(), Some((_, CommandRule::All))) {
+            rules.push("-@all".to_string());
+        }
+        for (allow, rule) in &self.commands {
+            rules.push(format!("{}{}", if *allow { '+' } else { '-' }, rule));
+        }
+        rules.join(" ")
+    }
+
+    pub fn keys_description(&self) -> String {
+        let keys: Vec<String> = self
+            .keys
+            .iter()
+            .map(|k| match (k.read, k.write) {
+                (true, false) => format!("%R~{}", k.pattern),
+                (false, true) => format!("%W~{}", k.pattern),
+                _ => format!("~{}", k.pattern),
+            })
+            .collect();
+        keys.join(" ")
+    }
+
+    pub fn channels_description(&self) -> String {
+        let channels: Vec<String> = self.channels.iter().map(|c| format!("&{}", c)).collect();
+        channels.join(" ")
+    }
+
+    // The rules recreating the user, as shown by ACL LIST and saved to the
+    // aclfile.
+    pub fn description(&self) -> String {
+        let mut rules: Vec<String> = self.flags().iter().map(|f| f.to_string()).collect();
+        rules.extend(self.passwords.iter().map(|p| format!("#{}", hex(p))));
+        for description in [self.keys_description(), self.channels_description()] {
+            if !description.is_empty() {
+                rules.push(description);
+            }
+        }
+        if self.channels.is_empty() {
+            rules.push("resetchannels".to_string());
+        }
+        rules.push(self.commands_description());
+        rules.join(" ")
+    }
+}
+
+impl fmt::Display for CommandRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandRule::All => f.write_str("@all"),
+            CommandRule::Category(category) => write!(f, "@{}", category),
+            CommandRule::Command(command) => f.write_str(command),
+            CommandRule::Subcommand(command, sub) => write!(f, "{}|{}", command, sub),
+        }
+    }
+}
+
+fn parse_command_rule(value: &str) -> Result<CommandRule, String> {
+    let unknown = || "Unknown command or category name in ACL".to_string();
+    if let Some(category) = value.strip_prefix('@') {
+        if category.eq_ignore_ascii_case("all") {
+            return Ok(CommandRule::All);
+        }
+        return CATEGORIES
+            .iter()
+            .find(|c| c.eq_ignore_ascii_case(category))
+            .map(|c| CommandRule::Category(c))
+            .ok_or_else(unknown);
+    }
+    match value.split_once('|') {
+        Some((command, sub)) => {
+            let command = find_command(command).ok_or_else(unknown)?;
+            let sub = command.find_subcommand(sub).ok_or_else(unknown)?;
+            Ok(CommandRule::Subcommand(command.name, sub.name))
+        }
+        None => find_command(value)
+            .map(|c| CommandRule::Command(c.name))
+            .ok_or_else(unknown),
+    }
+}
+
+fn parse_hash(s: &str) -> Result<[u8; 32], String> {
+    let invalid = || {
+        "The password hash must be exactly 64 characters and contain only lowercase hexadecimal characters".to_string()
+    };
+    if s.len() != 64 || !s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return Err(invalid());
+    }
+    let mut hash = [0; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(hash)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn syntax_error() -> String {
+    "Syntax error".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(rules: &str) -> User {
+        let mut user = User::new("app");
+        for rule in rules.split_whitespace() {
+            user.apply(rule).unwrap();
+        }
+        user
+    }
+
+    #[test]
+    fn test_command_rules() {
+        let app = user("+@all -@dangerous +info -config|set");
+        assert!(app.can_run("get", None));
+        assert!(app.can_run("info", None));
+        assert!(!app.can_run("shutdown", None));
+        assert!(!app.can_run("config", Some("get")));
+        assert!(app.can_run("acl", Some("whoami")));
+        assert!(app.can_run("nosuchcommand", None));
+
+        let app = user("+@read +config|get +@pubsub -publish");
+        assert!(app.can_run("get", None));
+        assert!(!app.can_run("set", None));
+        assert!(app.can_run("config", Some("get")));
+        assert!(!app.can_run("config", Some("set")));
+        assert!(app.can_run("subscribe", None));
+        assert!(!app.can_run("publish", None));
+        assert!(!app.can_run("nosuchcommand", None));
+        assert_eq!(
+            app.commands_description(),
+            "-@all +@read +config|get +@pubsub -publish"
+        );
+
+        assert!(!user("").can_run("get", None));
+        assert!(!user("+@all -@all").can_run("get", None));
+        assert_eq!(user("+get +@all -set").commands_description(), "+@all -set");
+    }
+
+    #[test]
+    fn test_key_and_channel_rules() {
+        let app = user("~cache:* %R~config:* %W~log:* &news.* &alerts");
+        assert!(app.can_access_key(b"cache:1", KeyAccess::Write));
+        assert!(app.can_access_key(b"config:a", KeyAccess::Read));
+        assert!(!app.can_access_key(b"config:a", KeyAccess::Write));
+        assert!(app.can_access_key(b"log:1", KeyAccess::Write));
+        assert!(!app.can_access_key(b"log:1", KeyAccess::Read));
+        assert!(!app.can_access_key(b"other", KeyAccess::Read));
+
+        assert!(app.can_access_channel(b"news.sport", false));
+        assert!(app.can_access_channel(b"news.*", true));
+        assert!(!app.can_access_channel(b"news.sp*", true));
+        assert!(!app.can_access_channel(b"weather", false));
+        assert!(user("allchannels").can_access_channel(b"a*", true));
+        assert!(!user("&* resetchannels").can_access_channel(b"a", false));
+    }
+
+    #[test]
+    fn test_passwords() {
+        let mut app = user(">one >two");
+        assert!(app.check_password(b"one"));
+        assert!(app.check_password(b"two"));
+        assert!(!app.check_password(b"three"));
+
+        app.apply("<one").unwrap();
+        assert!(!app.check_password(b"one"));
+        assert!(app.apply("<one").is_err());
+
+        let hash = hex(&hash_password(b"three"));
+        app.apply(&format!("#{}", hash)).unwrap();
+        assert!(app.check_password(b"three"));
+        assert!(app.apply("#ABC").is_err());
+
+        app.apply("nopass").unwrap();
+        assert!(app.check_password(b"anything"));
+        app.apply("resetpass").unwrap();
+        assert!(!app.check_password(b"anything"));
+    }
+
+    #[test]
+    fn test_description_round_trip() {
+        let app = user("on >secret ~cache:* %R~config:* &news.* +@read -get +config|get");
+        assert_eq!(
+            app.description(),
+            format!(
+                "on #{} ~cache:* %R~config:* &news.* -@all +@read -get +config|get",
+                hex(&hash_password(b"secret"))
+            )
+        );
+        assert_eq!(user(&app.description()), app);
+
+        assert_eq!(User::new("app").description(), "off resetchannels -@all");
+        assert_eq!(User::new_default().description(), "on nopass ~* &* +@all");
+        let default = User::new_default();
+        let mut reset = default.clone();
+        reset.apply("reset").unwrap();
+        assert_eq!(reset.description(), "off resetchannels -@all");
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let mut app = User::new("app");
+        for rule in [
+            "+nosuchcommand",
+            "+@nosuchcategory",
+            "+get|x",
+            "%X~a",
+            "%~a",
+            "bogus",
+            "",
+        ] {
+            assert!(app.apply(rule).is_err(), "{}", rule);
+        }
+        assert_eq!(app, User::new("app"));
+    }
+}
@@ -0,0 +1,30 @@
+use sha2::{Digest, Sha256};
+
+pub fn hash_password(password: &[u8]) -> [u8; 32] {
+    Sha256::digest(password).into()
+}
+
+// Passwords are compared by their hashes, like Redis does, so the length of
+// the expected password doesn't show in the timing, and in constant time so
+// neither does where they differ.
+pub fn digest_eq(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn password_eq(given: &[u8], expected: &[u8]) -> bool {
+        digest_eq(&hash_password(given), &hash_password(expected))
+    }
+
+    #[test]
+    fn test_digest_eq() {
+        assert!(password_eq(b"secret", b"secret"));
+        assert!(!password_eq(b"secret", b"Secret"));
+        assert!(!password_eq(b"secret", b"secret2"));
+        assert!(!password_eq(b"", b"secret"));
+        assert!(password_eq(b"", b""));
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock, RwLockReadGuard,
+    },
+};
+
+use dashmap::DashMap;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::{
+    acl::Acl,
+    config::{Config, ConfigError},
+    pubsub::PubSub,
+    session::{ClientHandle, CloseReason, QueuedFrame, Session},
+    shutdown::Shutdown,
+    stats::Stats,
+    storage::memory::InMemStore,
+    tls::Tls,
+    tracking::Tracking,
+};
+
+// Server wide state shared by every connection.
+#[derive(Debug, Clone, Default)]
+pub struct Backend(Arc<BackendInner>);
+
+#[derive(Debug, Default)]
+pub struct BackendInner {
+    config: RwLock<Config>,
+    pub store: InMemStore,
+    pub pubsub: PubSub,
+    pub tracking: Tracking,
+    pub stats: Stats,
+    pub shutdown: Shutdown,
+    pub tls: Tls,
+    pub acl: Acl,
+    clients: DashMap<u64, ClientHandle>,
+    next_client_id: AtomicU64,
+}
+
+impl Deref for Backend {
+    type Target = BackendInner;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Backend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let backend = Self::default();
+        backend.apply_config(&config);
+        backend.acl.set_requirepass(config.requirepass.as_deref());
+        *backend.config.write().unwrap() = config;
+        backend
+    }
+
+    pub fn config(&self) -> RwLockReadGuard<'_, Config> {
+        self.config.read().unwrap()
+    }
+
+    // Changes take effect right away, or not at all if `update` fails.
+    pub fn update_config<F>(&self, update: F) -> Result<(), ConfigError>
+    where
+        F: FnOnce(&mut Config) -> Result<(), ConfigError>,
+    {
+        let mut config = self.config.write().unwrap();
+        let mut updated = config.clone();
+        update(&mut updated)?;
+        self.apply_config(&updated);
+        // the default user's password is only reset when requirepass itself
+        // changes, not on every update
+        if updated.requirepass != config.requirepass {
+            self.acl.set_requirepass(updated.requirepass.as_deref());
+        }
+        *config = updated;
+        Ok(())
+    }
+
+    // Settings kept by the subsystems themselves, the others are read from
+    // the config when needed.
+    fn apply_config(&self, config: &Config) {
+        self.pubsub
+            .set_output_buffer_limit(config.client_output_buffer_limit);
+        self.pubsub
+            .set_notify_keyspace_events(config.notify_keyspace_events);
+        config.loglevel.set_current();
+    }
+
+    pub fn new_session(&self) -> (Session, UnboundedReceiver<QueuedFrame>) {
+        let id = self.next_client_id.fetch_add(1, Ordering::Relaxed) + 1;
+        Stats::incr(&self.stats.connected_clients);
+        Stats::incr(&self.stats.total_connections_received);
+        let (mut session, rx) = Session::new(id);
+        // without a password the default user needs no AUTH
+        session.set_authenticated(self.acl.default_user_needs_no_auth());
+        self.clients.insert(id, session.handle().clone());
+        (session, rx)
+    }
+
+    pub fn client(&self, id: u64) -> Option<ClientHandle> {
+        self.clients.get(&id).map(|c| c.value().clone())
+    }
+
+    // Release everything the connection registered in shared state.
+    pub fn close_session(&self, session: &mut Session) {
+        self.pubsub.unsubscribe_all(session);
+        self.pubsub.punsubscribe_all(session);
+        self.pubsub.sunsubscribe_all(session);
+        self.tracking.disable(session.id());
+        self.clients.remove(&session.id());
+
+        Stats::decr(&self.stats.connected_clients);
+        if session.handle().close_reason() == Some(CloseReason::OutputBufferLimit) {
+            Stats::incr(&self.stats.client_output_buffer_limit_disconnections);
+        }
+    }
+}
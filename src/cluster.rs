@@ -0,0 +1,52 @@
+pub const CLUSTER_SLOTS: u16 = 16384;
+
+// CRC16 (XMODEM variant) as used by Redis Cluster to map keys to slots.
+fn crc16(buf: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &b in buf {
+        crc ^= (b as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+// https://redis.io/docs/latest/operate/oss_and_stack/reference/cluster-spec/#hash-tags
+// Only the part between the first `{` and the following `}` is hashed, if it is not empty.
+pub fn key_hash_slot(key: &[u8]) -> u16 {
+    let hashed = key
+        .iter()
+        .position(|&b| b == b'{')
+        .and_then(|start| {
+            key[start + 1..]
+                .iter()
+                .position(|&b| b == b'}')
+                .filter(|&len| len > 0)
+                .map(|len| &key[start + 1..start + 1 + len])
+        })
+        .unwrap_or(key);
+
+    crc16(hashed) & (CLUSTER_SLOTS - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_hash_slot() {
+        assert_eq!(crc16(b"123456789"), 0x31c3);
+        assert_eq!(key_hash_slot(b"foo"), 12182);
+        assert_eq!(
+            key_hash_slot(b"{user1000}.following"),
+            key_hash_slot(b"user1000")
+        );
+        assert_eq!(key_hash_slot(b"foo{}{bar}"), key_hash_slot(b"foo{}{bar}"));
+        assert_ne!(key_hash_slot(b"foo{}{bar}"), key_hash_slot(b"bar"));
+    }
+}
@@ -0,0 +1,342 @@
+use std::fmt;
+
+use crate::{
+    acl::{commands_in_category, timestamp_ms, AclError, CATEGORIES},
+    backend::Backend,
+    resp::{BulkString, RespArray, RespFrame, RespMap, RespNullBulkString, SimpleError},
+    session::Session,
+};
+
+use super::{
+    extract_args, extract_string, lowercase, AclCommand, CommandError, CommandExecutor, RESP_OK,
+};
+
+impl CommandExecutor for AclCommand {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        match self {
+            AclCommand::SetUser { username, rules } => {
+                match backend.acl.set_user(&username, &rules) {
+                    Ok(()) => RESP_OK.clone(),
+                    Err(e) => error(e),
+                }
+            }
+            AclCommand::GetUser(username) => match backend.acl.user(&username) {
+                Some(user) => {
+                    let bulk_strings = |values: Vec<String>| -> Vec<RespFrame> {
+                        values
+                            .into_iter()
+                            .map(|v| BulkString::from(v).into())
+                            .collect()
+                    };
+                    let flags = user.flags().iter().map(|f| f.to_string()).collect();
+                    let mut map = RespMap::new();
+                    map.insert(
+                        BulkString::from("flags"),
+                        RespArray::new(bulk_strings(flags)),
+                    );
+                    map.insert(
+                        BulkString::from("passwords"),
+                        RespArray::new(bulk_strings(user.password_hashes())),
+                    );
+                    map.insert(
+                        BulkString::from("commands"),
+                        BulkString::from(user.commands_description()),
+                    );
+                    map.insert(
+                        BulkString::from("keys"),
+                        BulkString::from(user.keys_description()),
+                    );
+                    map.insert(
+                        BulkString::from("channels"),
+                        BulkString::from(user.channels_description()),
+                    );
+                    map.insert(BulkString::from("selectors"), RespArray::new(vec![]));
+                    map.into()
+                }
+                None => RespNullBulkString.into(),
+            },
+            AclCommand::DelUser(usernames) => match backend.acl.delete_users(&usernames) {
+                Ok(deleted) => RespFrame::Integer(deleted as i64),
+                Err(e) => error(e),
+            },
+            AclCommand::List => {
+                let users = backend
+                    .acl
+                    .users()
+                    .iter()
+                    .map(|u| {
+                        BulkString::from(format!("user {} {}", u.name(), u.description())).into()
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(users).into()
+            }
+            AclCommand::WhoAmI => BulkString::from(session.user()).into(),
+            AclCommand::Cat(None) => {
+                let categories = CATEGORIES
+                    .iter()
+                    .map(|c| BulkString::from(*c).into())
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(categories).into()
+            }
+            AclCommand::Cat(Some(category)) => {
+                let Some(category) = CATEGORIES.iter().find(|c| **c == category) else {
+                    return SimpleError::new(format!("ERR Unknown category '{}'", category)).into();
+                };
+                let commands = commands_in_category(category)
+                    .into_iter()
+                    .map(|c| BulkString::from(c).into())
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(commands).into()
+            }
+            AclCommand::Log(count) => {
+                let entries = backend
+                    .acl
+                    .log_entries(count)
+                    .into_iter()
+                    .map(|entry| {
+                        let age = entry.age().as_secs_f64();
+                        let mut map = RespMap::new();
+                        map.insert(BulkString::from("count"), entry.count as i64);
+                        map.insert(
+                            BulkString::from("reason"),
+                            BulkString::from(entry.reason.as_str()),
+                        );
+                        map.insert(BulkString::from("context"), BulkString::from("toplevel"));
+                        map.insert(BulkString::from("object"), BulkString::from(entry.object));
+                        map.insert(
+                            BulkString::from("username"),
+                            BulkString::from(entry.username),
+                        );
+                        map.insert(BulkString::from("age-seconds"), RespFrame::Double(age));
+                        map.insert(
+                            BulkString::from("client-info"),
+                            BulkString::from(entry.client_info),
+                        );
+                        map.insert(BulkString::from("entry-id"), entry.entry_id as i64);
+                        map.insert(
+                            BulkString::from("timestamp-created"),
+                            timestamp_ms(entry.created),
+                        );
+                        map.insert(
+                            BulkString::from("timestamp-last-updated"),
+                            timestamp_ms(entry.updated),
+                        );
+                        map.into()
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(entries).into()
+            }
+            AclCommand::LogReset => {
+                backend.acl.reset_log();
+                RESP_OK.clone()
+            }
+            AclCommand::Load | AclCommand::Save => {
+                let Some(path) = backend.config().aclfile.clone() else {
+                    return error(AclError::NoAclFile);
+                };
+                let ret = match self {
+                    AclCommand::Load => backend.acl.load(&path),
+                    _ => backend.acl.save(&path),
+                };
+                match ret {
+                    Ok(()) => RESP_OK.clone(),
+                    Err(e) => error(e),
+                }
+            }
+        }
+    }
+}
+
+fn error(e: AclError) -> RespFrame {
+    SimpleError::new(format!("ERR {}", e)).into()
+}
+
+// passwords given to SETUSER are kept out of the logs
+impl fmt::Debug for AclCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AclCommand::SetUser { username, rules } => {
+                let rules: Vec<&str> = rules
+                    .iter()
+                    .map(|rule| match rule.starts_with(['>', '<']) {
+                        true => "(redacted)",
+                        false => rule,
+                    })
+                    .collect();
+                f.debug_struct("SetUser")
+                    .field("username", username)
+                    .field("rules", &rules)
+                    .finish()
+            }
+            AclCommand::GetUser(username) => f.debug_tuple("GetUser").field(username).finish(),
+            AclCommand::DelUser(usernames) => f.debug_tuple("DelUser").field(usernames).finish(),
+            AclCommand::List => f.write_str("List"),
+            AclCommand::WhoAmI => f.write_str("WhoAmI"),
+            AclCommand::Cat(category) => f.debug_tuple("Cat").field(category).finish(),
+            AclCommand::Log(count) => f.debug_tuple("Log").field(count).finish(),
+            AclCommand::LogReset => f.write_str("LogReset"),
+            AclCommand::Load => f.write_str("Load"),
+            AclCommand::Save => f.write_str("Save"),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for AclCommand {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(extract_string)
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some(sub) = args.first().map(|s| lowercase(s.as_bytes())) else {
+            return Err(CommandError::WrongNumberOfArguments("acl".to_string()));
+        };
+        let wrong_args = || CommandError::WrongNumberOfArguments(format!("acl|{}", sub));
+
+        match sub.as_str() {
+            "setuser" if args.len() >= 2 => Ok(AclCommand::SetUser {
+                username: args[1].clone(),
+                rules: args[2..].to_vec(),
+            }),
+            "getuser" if args.len() == 2 => Ok(AclCommand::GetUser(args[1].clone())),
+            "deluser" if args.len() >= 2 => Ok(AclCommand::DelUser(args[1..].to_vec())),
+            "list" if args.len() == 1 => Ok(AclCommand::List),
+            "whoami" if args.len() == 1 => Ok(AclCommand::WhoAmI),
+            "cat" if args.len() <= 2 => {
+                Ok(AclCommand::Cat(args.get(1).map(|c| c.to_ascii_lowercase())))
+            }
+            "log" if args.len() == 1 => Ok(AclCommand::Log(10)),
+            "log" if args.len() == 2 && args[1].eq_ignore_ascii_case("reset") => {
+                Ok(AclCommand::LogReset)
+            }
+            "log" if args.len() == 2 => match args[1].parse::<usize>() {
+                Ok(count) => Ok(AclCommand::Log(count)),
+                Err(_) => Err(CommandError::InvalidArgument(
+                    "value is out of range, must be positive".to_string(),
+                )),
+            },
+            "load" if args.len() == 1 => Ok(AclCommand::Load),
+            "save" if args.len() == 1 => Ok(AclCommand::Save),
+            "setuser" | "getuser" | "deluser" | "list" | "whoami" | "cat" | "log" | "load"
+            | "save" => Err(wrong_args()),
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'",
+                sub
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{command::Command, resp::RespEncode};
+
+    use super::*;
+
+    fn run(backend: &Backend, session: &mut Session, args: &[&str]) -> Vec<u8> {
+        let frames: Vec<RespFrame> = args
+            .iter()
+            .map(|a| RespFrame::from(BulkString::from(*a)))
+            .collect();
+        match Command::try_from(RespArray::new(frames)) {
+            Ok(cmd) => cmd.execute(backend, session).encode(),
+            Err(e) => format!("-ERR {}\r\n", e).into_bytes(),
+        }
+    }
+
+    #[test]
+    fn test_acl_users() {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+        assert_eq!(
+            run(
+                &backend,
+                &mut session,
+                &["ACL", "SETUSER", "app", "on", ">secret", "~app:*", "+get"]
+            ),
+            b"+OK\r\n"
+        );
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "LIST"]),
+            b"*2\r\n$109\r\nuser app on #2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b ~app:* resetchannels -@all +get\r\n$34\r\nuser default on nopass ~* &* +@all\r\n".to_vec()
+        );
+        let getuser = run(&backend, &mut session, &["ACL", "GETUSER", "app"]);
+        assert!(getuser.starts_with(b"%6\r\n$5\r\nflags\r\n*1\r\n$2\r\non\r\n"));
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "GETUSER", "nobody"]),
+            b"$-1\r\n"
+        );
+        assert!(run(
+            &backend,
+            &mut session,
+            &["ACL", "SETUSER", "app", "+nosuch"]
+        )
+        .starts_with(b"-ERR Error in ACL SETUSER modifier '+nosuch'"));
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "WHOAMI"]),
+            b"$7\r\ndefault\r\n"
+        );
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "DELUSER", "app", "nobody"]),
+            b":+1\r\n"
+        );
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "DELUSER", "default"]),
+            b"-ERR The 'default' user cannot be removed\r\n"
+        );
+    }
+
+    #[test]
+    fn test_acl_cat_and_log() {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+        assert!(
+            run(&backend, &mut session, &["ACL", "CAT"]).starts_with(b"*21\r\n$8\r\nkeyspace\r\n")
+        );
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "CAT", "STRING"]),
+            b"*2\r\n$3\r\nget\r\n$3\r\nset\r\n"
+        );
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "CAT", "nosuch"]),
+            b"-ERR Unknown category 'nosuch'\r\n"
+        );
+
+        assert_eq!(
+            run(&backend, &mut session, &["AUTH", "app", "x"]).first(),
+            Some(&b'-')
+        );
+        let log = run(&backend, &mut session, &["ACL", "LOG"]);
+        assert!(
+            log.starts_with(b"*1\r\n%10\r\n$5\r\ncount\r\n:+1\r\n$6\r\nreason\r\n$4\r\nauth\r\n")
+        );
+        assert_eq!(run(&backend, &mut session, &["ACL", "LOG", "0"]), b"*0\r\n");
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "LOG", "RESET"]),
+            b"+OK\r\n"
+        );
+        assert_eq!(run(&backend, &mut session, &["ACL", "LOG"]), b"*0\r\n");
+
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "LOG", "-1"]),
+            b"-ERR Invalid Argument: value is out of range, must be positive\r\n"
+        );
+        assert_eq!(
+            run(&backend, &mut session, &["ACL", "WHOAMI", "x"]),
+            b"-ERR wrong number of arguments for 'acl|whoami' command\r\n"
+        );
+        assert!(run(&backend, &mut session, &["ACL", "SAVE"])
+            .starts_with(b"-ERR This Redis instance is not configured to use an ACL file."));
+    }
+
+    #[test]
+    fn test_setuser_debug_redacts_passwords() {
+        let cmd = AclCommand::SetUser {
+            username: "app".to_string(),
+            rules: vec!["on".to_string(), ">secret".to_string(), "<old".to_string()],
+        };
+        let debug = format!("{:?}", cmd);
+        assert!(!debug.contains("secret") && !debug.contains("old"));
+        assert!(debug.contains("\"on\""));
+    }
+}
@@ -0,0 +1,310 @@
+use bytes::Bytes;
+
+use crate::{
+    backend::Backend,
+    resp::{BulkString, RespArray, RespFrame, RespNullBulkString, SimpleError},
+    session::Session,
+    tracking::TrackingOptions,
+};
+
+use super::{
+    connection::validate_client_name, extract_args, extract_bytes, lowercase, ClientCommand,
+    CommandError, CommandExecutor, RESP_OK,
+};
+
+impl CommandExecutor for ClientCommand {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        match self {
+            ClientCommand::Id => RespFrame::Integer(session.id() as i64),
+            ClientCommand::Tracking { on: false, .. } => {
+                backend.tracking.disable(session.id());
+                RESP_OK.clone()
+            }
+            ClientCommand::Tracking {
+                on: true,
+                mut options,
+            } => {
+                if let Some(redirect) = options.redirect {
+                    if backend.client(redirect).is_none() {
+                        return error("ERR The client ID you want redirect to does not exist");
+                    }
+                }
+
+                if let Some(current) = backend.tracking.options(session.id()) {
+                    if current.bcast != options.bcast {
+                        return error("ERR You can't switch BCAST mode on/off before disabling tracking for this client, and then re-enabling it with a different mode.");
+                    }
+                    if current.optin != options.optin || current.optout != options.optout {
+                        return error("ERR You can't switch OPTIN/OPTOUT mode before disabling tracking for this client, and then re-enabling it with a different mode.");
+                    }
+                    let mut prefixes = current.prefixes;
+                    prefixes.extend(options.prefixes);
+                    options.prefixes = prefixes;
+                }
+
+                backend.tracking.enable(session.id(), options);
+                RESP_OK.clone()
+            }
+            ClientCommand::Caching(yes) => {
+                let options = backend.tracking.options(session.id()).unwrap_or_default();
+                if (yes && !options.optin) || (!yes && !options.optout) {
+                    return error("ERR CLIENT CACHING can be called only when the client is in tracking mode with OPTIN or OPTOUT mode enabled");
+                }
+                session.set_caching(Some(yes));
+                RESP_OK.clone()
+            }
+            ClientCommand::SetName(name) => {
+                if let Err(e) = validate_client_name(&name) {
+                    return e;
+                }
+                session.set_name((!name.is_empty()).then_some(name));
+                RESP_OK.clone()
+            }
+            ClientCommand::GetName => match session.name() {
+                Some(name) => BulkString::from(name).into(),
+                None => RespNullBulkString.into(),
+            },
+            ClientCommand::GetRedir => {
+                let redirect = match backend.tracking.options(session.id()) {
+                    Some(options) => options.redirect.map_or(0, |id| id as i64),
+                    None => -1,
+                };
+                RespFrame::Integer(redirect)
+            }
+        }
+    }
+}
+
+fn error(msg: &str) -> RespFrame {
+    SimpleError::new(msg).into()
+}
+
+impl TryFrom<RespArray> for ClientCommand {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(extract_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some(sub) = args.first().map(|s| lowercase(s)) else {
+            return Err(CommandError::WrongNumberOfArguments("client".to_string()));
+        };
+        let wrong_args = || CommandError::WrongNumberOfArguments(format!("client|{}", sub));
+
+        match sub.as_str() {
+            "id" if args.len() == 1 => Ok(ClientCommand::Id),
+            "getredir" if args.len() == 1 => Ok(ClientCommand::GetRedir),
+            "setname" if args.len() == 2 => {
+                Ok(ClientCommand::SetName(String::from_utf8(args[1].to_vec())?))
+            }
+            "getname" if args.len() == 1 => Ok(ClientCommand::GetName),
+            "caching" if args.len() == 2 => match lowercase(&args[1]).as_str() {
+                "yes" => Ok(ClientCommand::Caching(true)),
+                "no" => Ok(ClientCommand::Caching(false)),
+                _ => Err(CommandError::InvalidArgument("syntax error".to_string())),
+            },
+            "tracking" if args.len() >= 2 => parse_tracking(&args[1..]),
+            "id" | "getredir" | "setname" | "getname" | "caching" | "tracking" => Err(wrong_args()),
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'",
+                sub
+            ))),
+        }
+    }
+}
+
+fn parse_tracking(args: &[Bytes]) -> Result<ClientCommand, CommandError> {
+    let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+    let on = match lowercase(&args[0]).as_str() {
+        "on" => true,
+        "off" => false,
+        _ => return Err(syntax_error()),
+    };
+
+    let mut options = TrackingOptions::default();
+    let mut iter = args[1..].iter();
+    while let Some(arg) = iter.next() {
+        match lowercase(arg).as_str() {
+            "redirect" => {
+                let id = iter.next().ok_or_else(syntax_error)?;
+                let id = lowercase(id).parse::<u64>().map_err(|_| {
+                    CommandError::InvalidArgument("value is not an integer or out of range".into())
+                })?;
+                options.redirect = Some(id);
+            }
+            "prefix" => options
+                .prefixes
+                .push(iter.next().ok_or_else(syntax_error)?.clone()),
+            "bcast" => options.bcast = true,
+            "optin" => options.optin = true,
+            "optout" => options.optout = true,
+            "noloop" => options.noloop = true,
+            _ => return Err(syntax_error()),
+        }
+    }
+
+    if !options.bcast && !options.prefixes.is_empty() {
+        return Err(CommandError::InvalidArgument(
+            "PREFIX option requires BCAST mode to be enabled".to_string(),
+        ));
+    }
+    if options.optin && options.optout {
+        return Err(CommandError::InvalidArgument(
+            "You can't use both OPTIN and OPTOUT".to_string(),
+        ));
+    }
+    if options.bcast && (options.optin || options.optout) {
+        return Err(CommandError::InvalidArgument(
+            "OPTIN and OPTOUT are not compatible with BCAST".to_string(),
+        ));
+    }
+
+    Ok(ClientCommand::Tracking { on, options })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        command::{Command, Get, Set, Subscribe},
+        resp::{BulkString, RespPush},
+        tracking::{invalidation_frame, invalidation_message_frame},
+    };
+
+    use super::*;
+
+    fn array(args: &[&str]) -> RespArray {
+        RespArray::new(
+            args.iter()
+                .map(|a| BulkString::from(*a).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    fn run(backend: &Backend, session: &mut Session, args: &[&str]) -> RespFrame {
+        let cmd = Command::try_from(array(args)).unwrap();
+        cmd.execute(backend, session)
+    }
+
+    #[test]
+    fn test_tracking_redirect() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut reader, _reader_rx) = backend.new_session();
+        let (mut listener, mut listener_rx) = backend.new_session();
+        let (mut writer, _writer_rx) = backend.new_session();
+
+        Subscribe::try_from(array(&["subscribe", "__redis__:invalidate"]))?
+            .execute(&backend, &mut listener);
+        let redirect = listener.id().to_string();
+        assert_eq!(
+            run(
+                &backend,
+                &mut reader,
+                &["client", "tracking", "on", "redirect", &redirect]
+            ),
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            run(&backend, &mut reader, &["client", "getredir"]),
+            RespFrame::Integer(listener.id() as i64)
+        );
+
+        Get::try_from(array(&["get", "foo"]))?.execute(&backend, &mut reader);
+        Set::try_from(array(&["set", "foo", "1"]))?.execute(&backend, &mut writer);
+        assert_eq!(
+            listener_rx.try_recv()?.frame,
+            invalidation_message_frame(b"foo")
+        );
+
+        // invalidation happens once until the key is read again
+        Set::try_from(array(&["set", "foo", "2"]))?.execute(&backend, &mut writer);
+        assert!(listener_rx.try_recv().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tracking_bcast_optin() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut listener, mut rx) = backend.new_session();
+        let (mut bcast, _) = backend.new_session();
+        let (mut optin, _) = backend.new_session();
+        Subscribe::try_from(array(&["subscribe", "__redis__:invalidate"]))?
+            .execute(&backend, &mut listener);
+        let redirect = listener.id().to_string();
+
+        let redirect = redirect.as_str();
+        let args = ["client", "tracking", "on", "bcast", "prefix", "user:"];
+        run(
+            &backend,
+            &mut bcast,
+            &[&args[..], &["redirect", redirect]].concat(),
+        );
+        let args = ["client", "tracking", "on", "optin", "redirect", redirect];
+        run(&backend, &mut optin, &args);
+
+        // with OPTIN only reads following CLIENT CACHING YES are tracked
+        run(&backend, &mut optin, &["get", "a"]);
+        run(&backend, &mut optin, &["client", "caching", "yes"]);
+        run(&backend, &mut optin, &["get", "b"]);
+
+        for key in ["a", "b", "user:1", "other"] {
+            Set::try_from(array(&["set", key, "v"]))?.execute(&backend, &mut bcast);
+        }
+        let invalidated: Vec<RespFrame> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|m| m.frame)
+            .collect();
+        assert_eq!(
+            invalidated,
+            vec![
+                invalidation_message_frame(b"b"),
+                invalidation_message_frame(b"user:1")
+            ]
+        );
+
+        assert_eq!(
+            run(&backend, &mut bcast, &["client", "caching", "yes"]),
+            error("ERR CLIENT CACHING can be called only when the client is in tracking mode with OPTIN or OPTOUT mode enabled")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tracking_resp3() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut reader, mut rx) = backend.new_session();
+        let (mut writer, _) = backend.new_session();
+
+        reader.set_protocol(3);
+        run(
+            &backend,
+            &mut reader,
+            &["client", "tracking", "on", "noloop"],
+        );
+        run(&backend, &mut reader, &["get", "foo"]);
+        run(&backend, &mut reader, &["get", "bar"]);
+
+        // NOLOOP skips invalidations caused by the client itself
+        run(&backend, &mut reader, &["set", "bar", "1"]);
+        run(&backend, &mut writer, &["set", "foo", "1"]);
+        assert_eq!(rx.try_recv()?.frame, invalidation_frame(b"foo"));
+        assert!(rx.try_recv().is_err());
+
+        // the redirect target went away
+        let (target, _) = backend.new_session();
+        let redirect = target.id().to_string();
+        run(&backend, &mut reader, &["client", "tracking", "off"]);
+        run(
+            &backend,
+            &mut reader,
+            &["client", "tracking", "on", "redirect", &redirect],
+        );
+        let mut target = target;
+        backend.close_session(&mut target);
+        run(&backend, &mut reader, &["get", "foo"]);
+        run(&backend, &mut writer, &["set", "foo", "2"]);
+        assert_eq!(
+            rx.try_recv()?.frame,
+            RespPush::new(vec![BulkString::from("tracking-redir-broken").into()]).into()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt;
+
+use crate::{
+    backend::Backend,
+    config::{Config, ConfigError},
+    resp::{BulkString, RespArray, RespFrame, RespMap, SimpleError},
+    session::Session,
+};
+
+use super::{
+    extract_args, extract_bytes, lowercase, CommandError, CommandExecutor, ConfigCommand, RESP_OK,
+};
+
+impl CommandExecutor for ConfigCommand {
+    fn execute(self, backend: &Backend, _: &mut Session) -> RespFrame {
+        match self {
+            ConfigCommand::Get(patterns) => {
+                let patterns: Vec<&[u8]> = patterns.iter().map(|p| &p[..]).collect();
+                backend
+                    .config()
+                    .get_params(&patterns)
+                    .into_iter()
+                    .map(|(name, value)| (BulkString::from(name), BulkString::from(value)))
+                    .collect::<RespMap>()
+                    .into()
+            }
+            ConfigCommand::Set(params) => {
+                let update = |config: &mut Config| {
+                    config.set_params(&params)?;
+                    // setting a certificate file, even to the same path,
+                    // reads the certificates again
+                    match params.iter().find(|(name, _)| is_tls_param(name)) {
+                        Some((name, _)) => {
+                            backend
+                                .tls
+                                .reload(config)
+                                .map_err(|e| ConfigError::SetFailed {
+                                    name: name.clone(),
+                                    message: format!("Unable to update TLS configuration: {}", e),
+                                })
+                        }
+                        None => Ok(()),
+                    }
+                };
+                match backend.update_config(update) {
+                    Ok(()) => RESP_OK.clone(),
+                    Err(e) => SimpleError::new(format!("ERR {}", e)).into(),
+                }
+            }
+            ConfigCommand::Rewrite => {
+                let config = backend.config();
+                let Some(path) = &config.config_file else {
+                    return SimpleError::new("ERR The server is running without a config file")
+                        .into();
+                };
+                match config.rewrite(path) {
+                    Ok(()) => RESP_OK.clone(),
+                    Err(e) => SimpleError::new(format!("ERR Rewriting config file: {}", e)).into(),
+                }
+            }
+            ConfigCommand::ResetStat => {
+                backend.stats.reset();
+                RESP_OK.clone()
+            }
+        }
+    }
+}
+
+fn is_tls_param(name: &str) -> bool {
+    name.get(..4)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("tls-"))
+}
+
+fn is_password_param(name: &str) -> bool {
+    name.eq_ignore_ascii_case("requirepass")
+}
+
+// passwords given to CONFIG SET are kept out of the logs
+impl fmt::Debug for ConfigCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigCommand::Get(patterns) => f.debug_tuple("Get").field(patterns).finish(),
+            ConfigCommand::Set(params) => {
+                let params: Vec<(&str, &str)> = params
+                    .iter()
+                    .map(|(name, value)| match is_password_param(name) {
+                        true => (name.as_str(), "(redacted)"),
+                        false => (name.as_str(), value.as_str()),
+                    })
+                    .collect();
+                f.debug_tuple("Set").field(&params).finish()
+            }
+            ConfigCommand::Rewrite => f.write_str("Rewrite"),
+            ConfigCommand::ResetStat => f.write_str("ResetStat"),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ConfigCommand {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(extract_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some(sub) = args.first().map(|s| lowercase(s)) else {
+            return Err(CommandError::WrongNumberOfArguments("config".to_string()));
+        };
+        let wrong_args = || CommandError::WrongNumberOfArguments(format!("config|{}", sub));
+
+        match sub.as_str() {
+            "get" if args.len() >= 2 => Ok(ConfigCommand::Get(args[1..].to_vec())),
+            "set" if args.len() >= 3 && args.len() % 2 == 1 => {
+                let params = args[1..]
+                    .chunks(2)
+                    .map(|pair| {
+                        Ok((
+                            String::from_utf8(pair[0].to_vec())?,
+                            String::from_utf8(pair[1].to_vec())?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, CommandError>>()?;
+                Ok(ConfigCommand::Set(params))
+            }
+            "rewrite" if args.len() == 1 => Ok(ConfigCommand::Rewrite),
+            "resetstat" if args.len() == 1 => Ok(ConfigCommand::ResetStat),
+            "get" | "set" | "rewrite" | "resetstat" => Err(wrong_args()),
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'",
+                sub
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{command::Command, stats::Stats};
+
+    use super::*;
+
+    fn config_command(args: &[&str]) -> Result<Command, CommandError> {
+        let mut frames = vec![BulkString::from("CONFIG").into()];
+        frames.extend(args.iter().map(|arg| BulkString::from(*arg).into()));
+        Command::try_from(RespFrame::from(RespArray::new(frames)))
+    }
+
+    fn run(backend: &Backend, session: &mut Session, args: &[&str]) -> RespFrame {
+        config_command(args).unwrap().execute(backend, session)
+    }
+
+    #[test]
+    fn test_config_get_set() {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+
+        assert_eq!(
+            run(
+                &backend,
+                &mut session,
+                &["SET", "maxmemory", "10mb", "notify-keyspace-events", "KA"]
+            ),
+            RESP_OK.clone()
+        );
+        let mut expected = RespMap::new();
+        expected.insert(BulkString::from("maxmemory"), BulkString::from("10485760"));
+        expected.insert(
+            BulkString::from("notify-keyspace-events"),
+            BulkString::from("AK"),
+        );
+        assert_eq!(
+            run(&backend, &mut session, &["GET", "maxmem*", "notify-*"]),
+            expected.into()
+        );
+        // subsystems see the new values
+        assert_eq!(backend.pubsub.notify_keyspace_events().to_string(), "AK");
+
+        assert_eq!(
+            run(&backend, &mut session, &["SET", "maxmemory", "1gb", "port", "1"]),
+            SimpleError::new("ERR CONFIG SET failed (possibly related to argument 'port') - can't set immutable config").into()
+        );
+        assert_eq!(backend.config().maxmemory, 10 * 1024 * 1024);
+
+        assert!(config_command(&["SET", "maxmemory"]).is_err());
+        assert!(config_command(&["GET"]).is_err());
+        assert!(config_command(&["FOO"]).is_err());
+    }
+
+    #[test]
+    fn test_config_set_debug_redacts_password() {
+        let cmd = config_command(&["SET", "maxmemory", "1gb", "REQUIREPASS", "secret"]).unwrap();
+        let debug = format!("{:?}", cmd);
+        assert!(!debug.contains("secret"), "{}", debug);
+        assert!(debug.contains("(redacted)"));
+        assert!(debug.contains("1gb"));
+    }
+
+    #[test]
+    fn test_config_rewrite() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+        assert_eq!(
+            run(&backend, &mut session, &["REWRITE"]),
+            SimpleError::new("ERR The server is running without a config file").into()
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("simple-redis-config-{}.conf", std::process::id()));
+        fs::write(&path, "# limits\nmaxclients 100\n")?;
+        let backend = Backend::with_config(Config::from_args([path.display().to_string()])?);
+        run(&backend, &mut session, &["SET", "maxclients", "50"]);
+        let reply = run(&backend, &mut session, &["REWRITE"]);
+        let text = fs::read_to_string(&path);
+        fs::remove_file(&path)?;
+
+        assert_eq!(reply, RESP_OK.clone());
+        assert_eq!(text?, "# limits\nmaxclients 50\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_resetstat() {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+        Stats::incr(&backend.stats.total_commands_processed);
+
+        assert_eq!(run(&backend, &mut session, &["RESETSTAT"]), RESP_OK.clone());
+        assert_eq!(Stats::get(&backend.stats.total_commands_processed), 0);
+        assert_eq!(Stats::get(&backend.stats.total_connections_received), 0);
+        assert_eq!(Stats::get(&backend.stats.connected_clients), 1);
+    }
+}
@@ -0,0 +1,309 @@
+use std::fmt;
+
+use crate::{
+    acl::DenyReason,
+    backend::Backend,
+    resp::{BulkString, RespArray, RespFrame, RespMap, SimpleError},
+    session::Session,
+};
+
+use super::{
+    extract_args, extract_bytes, extract_string, Auth, CommandError, CommandExecutor, Hello,
+    RESP_OK,
+};
+
+impl CommandExecutor for Auth {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let default_nopass = backend.acl.user("default").is_some_and(|u| u.is_nopass());
+        if self.username.is_none() && default_nopass {
+            return SimpleError::new("ERR AUTH <password> called without any password configured for the default user. Are you sure your configuration is correct?").into();
+        }
+        let username = self.username.as_deref().unwrap_or(b"default");
+        match authenticate(backend, session, username, &self.password) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e,
+        }
+    }
+}
+
+// Switch the connection to `username`. Failures are recorded in the ACL LOG.
+fn authenticate(
+    backend: &Backend,
+    session: &mut Session,
+    username: &[u8],
+    password: &[u8],
+) -> Result<(), RespFrame> {
+    let username = String::from_utf8_lossy(username);
+    if !backend.acl.authenticate(username.as_bytes(), password) {
+        backend.acl.log_denied(
+            DenyReason::Auth,
+            "AUTH".to_string(),
+            username.into_owned(),
+            client_info(session),
+            backend.config().acllog_max_len,
+        );
+        return Err(SimpleError::new(
+            "WRONGPASS invalid username-password pair or user is disabled.",
+        )
+        .into());
+    }
+    session.set_user(username);
+    session.set_authenticated(true);
+    Ok(())
+}
+
+// Identifies the client in ACL LOG entries.
+pub(crate) fn client_info(session: &Session) -> String {
+    format!(
+        "id={} name={} user={}",
+        session.id(),
+        session.name().unwrap_or_default(),
+        session.user()
+    )
+}
+
+// passwords are kept out of the logs
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Auth")
+            .field("username", &self.username)
+            .field("password", &"(redacted)")
+            .finish()
+    }
+}
+
+impl fmt::Debug for Hello {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hello")
+            .field("protover", &self.protover)
+            .field(
+                "auth",
+                &self
+                    .auth
+                    .as_ref()
+                    .map(|(username, _)| (username, "(redacted)")),
+            )
+            .field("setname", &self.setname)
+            .finish()
+    }
+}
+
+impl TryFrom<RespArray> for Auth {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?
+            .into_iter()
+            .map(extract_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+        match args.len() {
+            1 => Ok(Auth {
+                username: None,
+                password: args.remove(0),
+            }),
+            2 => Ok(Auth {
+                password: args.remove(1),
+                username: Some(args.remove(0)),
+            }),
+            0 => Err(CommandError::WrongNumberOfArguments("auth".to_string())),
+            _ => Err(CommandError::InvalidArgument("syntax error".to_string())),
+        }
+    }
+}
+
+impl CommandExecutor for Hello {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        if let Some(protover) = self.protover {
+            if !(2..=3).contains(&protover) {
+                return SimpleError::new("NOPROTO unsupported protocol version").into();
+            }
+        }
+
+        if let Some((username, password)) = &self.auth {
+            if let Err(e) = authenticate(backend, session, username.as_bytes(), password.as_bytes())
+            {
+                return e;
+            }
+        }
+        if !session.is_authenticated() {
+            return SimpleError::new("NOAUTH HELLO must be called with the client already authenticated, otherwise the HELLO <proto> AUTH <user> <pass> option can be used to authenticate the client and select the RESP protocol version at the same time").into();
+        }
+
+        if let Some(name) = self.setname {
+            if let Err(e) = validate_client_name(&name) {
+                return e;
+            }
+            session.set_name((!name.is_empty()).then_some(name));
+        }
+
+        if let Some(protover) = self.protover {
+            session.set_protocol(protover as u8);
+        }
+
+        let mut info = RespMap::new();
+        info.insert(BulkString::from("server"), BulkString::from("redis"));
+        info.insert(BulkString::from("version"), BulkString::from("7.2.0"));
+        info.insert(BulkString::from("proto"), session.protocol() as i64);
+        info.insert(BulkString::from("id"), session.id() as i64);
+        info.insert(BulkString::from("mode"), BulkString::from("standalone"));
+        info.insert(BulkString::from("role"), BulkString::from("master"));
+        info.insert(BulkString::from("modules"), RespArray::new(vec![]));
+        info.into()
+    }
+}
+
+pub(crate) fn validate_client_name(name: &str) -> Result<(), RespFrame> {
+    if name.bytes().any(|b| !(b'!'..=b'~').contains(&b)) {
+        return Err(SimpleError::new(
+            "ERR Client names cannot contain spaces, newlines or special characters.",
+        )
+        .into());
+    }
+    Ok(())
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(extract_string)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut args = args.into_iter();
+
+        let mut hello = Hello {
+            protover: None,
+            auth: None,
+            setname: None,
+        };
+        let Some(protover) = args.next() else {
+            return Ok(hello);
+        };
+        hello.protover = Some(protover.parse().map_err(|_| {
+            CommandError::InvalidArgument(
+                "Protocol version is not an integer or out of range".to_string(),
+            )
+        })?);
+
+        let syntax_error = |opt: &str| {
+            CommandError::InvalidArgument(format!("Syntax error in HELLO option '{}'", opt))
+        };
+        while let Some(opt) = args.next() {
+            match opt.to_ascii_lowercase().as_str() {
+                "auth" => match (args.next(), args.next()) {
+                    (Some(username), Some(password)) => hello.auth = Some((username, password)),
+                    _ => return Err(syntax_error(&opt)),
+                },
+                "setname" => match args.next() {
+                    Some(name) => hello.setname = Some(name),
+                    None => return Err(syntax_error(&opt)),
+                },
+                _ => return Err(syntax_error(&opt)),
+            }
+        }
+        Ok(hello)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{command::Command, config::Config, resp::RespEncode};
+
+    use super::*;
+
+    fn hello(args: &[&str]) -> Hello {
+        let mut frames: Vec<RespFrame> = vec![BulkString::from("hello").into()];
+        frames.extend(args.iter().map(|a| BulkString::from(*a).into()));
+        Hello::try_from(RespArray::new(frames)).unwrap()
+    }
+
+    #[test]
+    fn test_hello() {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+
+        let RespFrame::Map(info) = hello(&[]).execute(&backend, &mut session) else {
+            panic!("HELLO must reply with a map");
+        };
+        assert_eq!(info.get_str("proto"), Some(&RespFrame::Integer(2)));
+
+        let reply = hello(&["3", "AUTH", "default", "pass", "SETNAME", "app"])
+            .execute(&backend, &mut session);
+        let RespFrame::Map(info) = reply else {
+            panic!("HELLO must reply with a map");
+        };
+        assert_eq!(info.get_str("proto"), Some(&RespFrame::Integer(3)));
+        assert_eq!(session.protocol(), 3);
+        assert_eq!(session.name(), Some("app"));
+
+        assert_eq!(
+            hello(&["4"]).execute(&backend, &mut session).encode(),
+            b"-NOPROTO unsupported protocol version\r\n"
+        );
+        assert_eq!(session.protocol(), 3);
+    }
+
+    fn run(backend: &Backend, session: &mut Session, args: &[&str]) -> Vec<u8> {
+        let frames: Vec<RespFrame> = args
+            .iter()
+            .map(|a| RespFrame::from(BulkString::from(*a)))
+            .collect();
+        match Command::try_from(RespArray::new(frames)) {
+            Ok(cmd) => cmd.execute(backend, session).encode(),
+            Err(e) => format!("-ERR {}\r\n", e).into_bytes(),
+        }
+    }
+
+    #[test]
+    fn test_auth() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+        assert!(session.is_authenticated());
+        assert!(run(&backend, &mut session, &["AUTH", "pass"])
+            .starts_with(b"-ERR AUTH <password> called without any password configured"));
+        assert_eq!(
+            run(&backend, &mut session, &["AUTH", "default", "any"]),
+            b"+OK\r\n"
+        );
+
+        let backend = Backend::with_config(Config::from_args(
+            ["--requirepass", "s3cret"].map(String::from),
+        )?);
+        let (mut session, _rx) = backend.new_session();
+        assert!(!session.is_authenticated());
+        for args in [&["AUTH", "wrong"][..], &["AUTH", "app", "s3cret"]] {
+            assert_eq!(
+                run(&backend, &mut session, args),
+                b"-WRONGPASS invalid username-password pair or user is disabled.\r\n"
+            );
+        }
+        assert!(run(&backend, &mut session, &["HELLO", "3"])
+            .starts_with(b"-NOAUTH HELLO must be called"));
+        assert!(!session.is_authenticated());
+        assert_eq!(run(&backend, &mut session, &["AUTH", "s3cret"]), b"+OK\r\n");
+        assert!(session.is_authenticated());
+
+        let (mut session, _rx) = backend.new_session();
+        run(
+            &backend,
+            &mut session,
+            &["HELLO", "3", "AUTH", "default", "s3cret"],
+        );
+        assert!(session.is_authenticated());
+        assert_eq!(session.protocol(), 3);
+        let cmd = Command::try_from(RespArray::new(vec![
+            BulkString::from("AUTH").into(),
+            BulkString::from("s3cret").into(),
+        ]))?;
+        assert!(!format!("{:?}", cmd).contains("s3cret"));
+
+        assert_eq!(
+            run(&backend, &mut session, &["AUTH"]),
+            b"-ERR wrong number of arguments for 'auth' command\r\n"
+        );
+        assert_eq!(
+            run(&backend, &mut session, &["AUTH", "a", "b", "c"]),
+            b"-ERR Invalid Argument: syntax error\r\n"
+        );
+        Ok(())
+    }
+}
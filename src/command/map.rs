@@ -1,23 +1,40 @@
 use crate::{
+    backend::Backend,
+    pubsub::NotifyFlags,
     resp::{RespArray, RespFrame, RespNull},
-    storage::memory::InMemStore,
+    session::Session,
 };
 
-use super::{extract_args, CommandError, CommandExecutor, Get, Set, RESP_OK};
+use super::{extract_args, extract_bytes, CommandError, CommandExecutor, Get, Set, RESP_OK};
 
 impl CommandExecutor for Get {
-    fn execute(self, store: &InMemStore) -> RespFrame {
-        let value = store.get(&self.key);
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        backend.tracking.track_read(session, &self.key);
+        let value = backend.store.get(&self.key);
         match value {
             Some(v) => v,
-            None => RespFrame::Null(RespNull),
+            None => {
+                backend.pubsub.notify_keyspace_event(
+                    NotifyFlags::KEY_MISS,
+                    "keymiss",
+                    &self.key,
+                    0,
+                );
+                RespFrame::Null(RespNull)
+            }
         }
     }
 }
 
 impl CommandExecutor for Set {
-    fn execute(self, store: &InMemStore) -> RespFrame {
-        store.set(self.key, self.value);
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let key = self.key.clone();
+        let pubsub = &backend.pubsub;
+        if backend.store.set(self.key, self.value).is_none() {
+            pubsub.notify_keyspace_event(NotifyFlags::NEW, "new", &key, 0);
+        }
+        pubsub.notify_keyspace_event(NotifyFlags::STRING, "set", &key, 0);
+        backend.tracking.invalidate_key(backend, &key, session.id());
         RESP_OK.clone()
     }
 }
@@ -28,7 +45,7 @@ impl TryFrom<RespArray> for Get {
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Get {
-                key: String::from_utf8(key.0)?,
+                key: key.into_bytes(),
             }),
             _ => Err(CommandError::InvalidArgument(
                 "Get command must have a BulkString as the first argument".to_string(),
@@ -36,3 +53,139 @@ impl TryFrom<RespArray> for Get {
         }
     }
 }
+
+impl TryFrom<RespArray> for Set {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(key), Some(value @ RespFrame::BulkString(_)), None) => Ok(Set {
+                key: extract_bytes(key)?,
+                value,
+            }),
+            _ => Err(CommandError::WrongNumberOfArguments("set".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{Bytes, BytesMut};
+
+    use crate::{
+        pubsub::pmessage_frame,
+        resp::{BulkString, RespDecode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_set_get_keyspace_events() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut session, mut rx) = backend.new_session();
+        backend
+            .pubsub
+            .set_notify_keyspace_events("KE$mn".parse().unwrap());
+        backend
+            .pubsub
+            .psubscribe(&mut session, Bytes::from_static(b"__key*"));
+
+        let cmd = Set::try_from(RespArray::new(vec![
+            BulkString::from("set").into(),
+            BulkString::from("foo").into(),
+            BulkString::from("bar").into(),
+        ]))?;
+        assert_eq!(cmd.execute(&backend, &mut session), RESP_OK.clone());
+
+        let events: Vec<RespFrame> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|m| m.frame)
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                pmessage_frame(b"__key*", b"__keyspace@0__:foo", "new".into()),
+                pmessage_frame(b"__key*", b"__keyevent@0__:new", "foo".into()),
+                pmessage_frame(b"__key*", b"__keyspace@0__:foo", "set".into()),
+                pmessage_frame(b"__key*", b"__keyevent@0__:set", "foo".into()),
+            ]
+        );
+
+        let cmd = Get::try_from(RespArray::new(vec![
+            BulkString::from("get").into(),
+            BulkString::from("missing").into(),
+        ]))?;
+        assert_eq!(cmd.execute(&backend, &mut session), RespNull.into());
+        assert_eq!(
+            rx.try_recv()?.frame,
+            pmessage_frame(b"__key*", b"__keyspace@0__:missing", "keymiss".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_get_binary_key() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut session, mut rx) = backend.new_session();
+        backend
+            .pubsub
+            .set_notify_keyspace_events("K$".parse().unwrap());
+        backend
+            .pubsub
+            .psubscribe(&mut session, Bytes::from_static(b"__keyspace@0__:*"));
+
+        let mut buf = BytesMut::from(&b"*3\r\n$3\r\nset\r\n$3\r\n\x00\xff\n\r\n$1\r\nv\r\n"[..]);
+        let cmd = Set::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend, &mut session), RESP_OK.clone());
+        assert_eq!(
+            rx.try_recv()?.frame,
+            pmessage_frame(
+                b"__keyspace@0__:*",
+                b"__keyspace@0__:\x00\xff\n",
+                "set".into()
+            )
+        );
+
+        let cmd = Get::try_from(RespArray::new(vec![
+            BulkString::from("get").into(),
+            BulkString::from(&b"\x00\xff\n"[..]).into(),
+        ]))?;
+        assert_eq!(
+            cmd.execute(&backend, &mut session),
+            BulkString::from("v").into()
+        );
+
+        // a key that is not valid UTF-8 is not mangled into another one
+        let cmd = Get::try_from(RespArray::new(vec![
+            BulkString::from("get").into(),
+            BulkString::from(&b"\x00\xef\xbf\xbd\n"[..]).into(),
+        ]))?;
+        assert_eq!(cmd.execute(&backend, &mut session), RespNull.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_get_zero_copy() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+
+        // small values are copied into the store, large ones are not
+        let value = vec![b'v'; 16 * 1024];
+        let mut buf = BytesMut::from(&b"*3\r\n$3\r\nset\r\n$3\r\nfoo\r\n$16384\r\n"[..]);
+        buf.extend_from_slice(&value);
+        buf.extend_from_slice(b"\r\n");
+        let payload = buf[buf.len() - value.len() - 2..].as_ptr();
+        let cmd = Set::try_from(RespArray::decode(&mut buf)?)?;
+        cmd.execute(&backend, &mut session);
+
+        let cmd = Get::try_from(RespArray::new(vec![
+            BulkString::from("get").into(),
+            BulkString::from("foo").into(),
+        ]))?;
+        // the value is neither copied into the store nor out of it
+        match cmd.execute(&backend, &mut session) {
+            RespFrame::BulkString(value) => assert_eq!(value.as_ptr(), payload),
+            frame => panic!("unexpected reply: {:?}", frame),
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,20 @@
+pub mod acl;
+pub mod client;
+pub mod config;
+pub mod connection;
 pub mod map;
+pub mod pubsub;
+pub mod server;
 
 use crate::{
-    resp::{RespArray, RespError, RespFrame, SimpleString},
-    storage::memory::InMemStore,
+    acl::KeyAccess,
+    backend::Backend,
+    resp::{BulkString, RespArray, RespError, RespFrame, SimpleString},
+    session::Session,
+    shutdown::ShutdownFlags,
+    tracking::TrackingOptions,
 };
+use bytes::Bytes;
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
 use std::string::FromUtf8Error;
@@ -15,7 +26,7 @@ lazy_static! {
 
 #[enum_dispatch]
 pub trait CommandExecutor {
-    fn execute(self, store: &InMemStore) -> RespFrame;
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame;
 }
 
 #[enum_dispatch(CommandExecutor)]
@@ -26,6 +37,22 @@ pub enum Command {
     // HGet(HGet),
     // HSet(HGet),
     // HGetAll(HGet),
+    Subscribe(Subscribe),
+    Unsubscribe(Unsubscribe),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    Publish(Publish),
+    SSubscribe(SSubscribe),
+    SUnsubscribe(SUnsubscribe),
+    SPublish(SPublish),
+    PubSub(PubSubCommand),
+    Info(Info),
+    Shutdown(Shutdown),
+    Client(ClientCommand),
+    Config(ConfigCommand),
+    Hello(Hello),
+    Auth(Auth),
+    Acl(AclCommand),
     Unknown(Unknown),
 }
 
@@ -35,6 +62,8 @@ pub enum CommandError {
     InvalidCommand(String),
     #[error("Invalid Argument: {0}")]
     InvalidArgument(String),
+    #[error("wrong number of arguments for '{0}' command")]
+    WrongNumberOfArguments(String),
 
     #[error("{0}")]
     RespError(#[from] RespError),
@@ -44,41 +73,154 @@ pub enum CommandError {
 
 #[derive(Debug)]
 pub struct Get {
-    key: String,
+    key: Bytes,
 }
 
 #[derive(Debug)]
 pub struct Set {
-    key: String,
+    key: Bytes,
     value: RespFrame,
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct HGet {
-    key: String,
-    field: String,
+    key: Bytes,
+    field: Bytes,
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct HSet {
-    key: String,
-    field: String,
+    key: Bytes,
+    field: Bytes,
     value: RespFrame,
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct HGetAll {
-    key: String,
+    key: Bytes,
 }
 
 #[derive(Debug)]
-pub struct Unknown;
+pub struct Subscribe {
+    channels: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct Unsubscribe {
+    channels: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct PSubscribe {
+    patterns: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    patterns: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct Publish {
+    channel: Bytes,
+    message: BulkString,
+}
+
+#[derive(Debug)]
+pub struct SSubscribe {
+    channels: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SUnsubscribe {
+    channels: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct SPublish {
+    channel: Bytes,
+    message: BulkString,
+}
+
+#[derive(Debug)]
+pub enum PubSubCommand {
+    Channels { pattern: Option<Bytes> },
+    NumSub { channels: Vec<Bytes> },
+    NumPat,
+    ShardChannels { pattern: Option<Bytes> },
+    ShardNumSub { channels: Vec<Bytes> },
+}
+
+#[derive(Debug)]
+pub enum ClientCommand {
+    Id,
+    Tracking { on: bool, options: TrackingOptions },
+    Caching(bool),
+    GetRedir,
+    SetName(String),
+    GetName,
+}
+
+pub enum ConfigCommand {
+    Get(Vec<Bytes>),
+    Set(Vec<(String, String)>),
+    Rewrite,
+    ResetStat,
+}
+
+pub struct Hello {
+    protover: Option<i64>,
+    auth: Option<(String, String)>,
+    setname: Option<String>,
+}
+
+pub enum AclCommand {
+    SetUser {
+        username: String,
+        rules: Vec<String>,
+    },
+    GetUser(String),
+    DelUser(Vec<String>),
+    List,
+    WhoAmI,
+    Cat(Option<String>),
+    Log(usize),
+    LogReset,
+    Load,
+    Save,
+}
+
+pub struct Auth {
+    username: Option<Bytes>,
+    password: Bytes,
+}
+
+#[derive(Debug)]
+pub struct Info {
+    sections: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Shutdown {
+    flags: ShutdownFlags,
+    abort: bool,
+}
+
+#[derive(Debug)]
+pub struct Unknown {
+    name: String,
+}
 
 impl TryFrom<RespFrame> for Command {
     type Error = CommandError;
     fn try_from(value: RespFrame) -> Result<Self, Self::Error> {
         match value {
             RespFrame::Array(arr) => arr.try_into(),
+            // attributes only carry auxiliary data about the request
+            RespFrame::Attribute(attr) => attr.into_parts().1.try_into(),
             _ => Err(CommandError::InvalidCommand(
                 "Command must be an array".to_string(),
             )),
@@ -90,9 +232,29 @@ impl TryFrom<RespArray> for Command {
     type Error = CommandError;
     fn try_from(v: RespArray) -> Result<Self, Self::Error> {
         match v.first() {
-            Some(RespFrame::BulkString(ref cmd)) => match cmd.as_ref() {
+            Some(RespFrame::BulkString(ref cmd)) => match cmd.to_ascii_lowercase().as_slice() {
                 b"get" => Ok(Get::try_from(v)?.into()),
-                _ => Ok(Unknown.into()),
+                b"set" => Ok(Set::try_from(v)?.into()),
+                b"subscribe" => Ok(Subscribe::try_from(v)?.into()),
+                b"unsubscribe" => Ok(Unsubscribe::try_from(v)?.into()),
+                b"psubscribe" => Ok(PSubscribe::try_from(v)?.into()),
+                b"punsubscribe" => Ok(PUnsubscribe::try_from(v)?.into()),
+                b"publish" => Ok(Publish::try_from(v)?.into()),
+                b"ssubscribe" => Ok(SSubscribe::try_from(v)?.into()),
+                b"sunsubscribe" => Ok(SUnsubscribe::try_from(v)?.into()),
+                b"spublish" => Ok(SPublish::try_from(v)?.into()),
+                b"pubsub" => Ok(PubSubCommand::try_from(v)?.into()),
+                b"info" => Ok(Info::try_from(v)?.into()),
+                b"shutdown" => Ok(Shutdown::try_from(v)?.into()),
+                b"client" => Ok(ClientCommand::try_from(v)?.into()),
+                b"config" => Ok(ConfigCommand::try_from(v)?.into()),
+                b"hello" => Ok(Hello::try_from(v)?.into()),
+                b"auth" => Ok(Auth::try_from(v)?.into()),
+                b"acl" => Ok(AclCommand::try_from(v)?.into()),
+                name => Ok(Unknown {
+                    name: lowercase(name),
+                }
+                .into()),
             },
             _ => Err(CommandError::InvalidCommand(
                 "Command must have a BulkString as the first argument".to_string(),
@@ -101,8 +263,116 @@ impl TryFrom<RespArray> for Command {
     }
 }
 
+impl Command {
+    // Commands a RESP2 client may still send while subscribed to channels.
+    pub fn allowed_in_subscribed_context(&self) -> bool {
+        matches!(
+            self,
+            Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::SSubscribe(_)
+                | Command::SUnsubscribe(_)
+        )
+    }
+
+    // Commands a client may send before it has authenticated. They are
+    // allowed whatever the permissions of the user.
+    pub fn allowed_unauthenticated(&self) -> bool {
+        matches!(self, Command::Auth(_) | Command::Hello(_))
+    }
+
+    // The command and subcommand names ACL rules refer to.
+    pub fn acl_name(&self) -> (&str, Option<&'static str>) {
+        match self {
+            Command::Get(_) => ("get", None),
+            Command::Set(_) => ("set", None),
+            Command::Subscribe(_) => ("subscribe", None),
+            Command::Unsubscribe(_) => ("unsubscribe", None),
+            Command::PSubscribe(_) => ("psubscribe", None),
+            Command::PUnsubscribe(_) => ("punsubscribe", None),
+            Command::Publish(_) => ("publish", None),
+            Command::SSubscribe(_) => ("ssubscribe", None),
+            Command::SUnsubscribe(_) => ("sunsubscribe", None),
+            Command::SPublish(_) => ("spublish", None),
+            Command::PubSub(cmd) => (
+                "pubsub",
+                Some(match cmd {
+                    PubSubCommand::Channels { .. } => "channels",
+                    PubSubCommand::NumSub { .. } => "numsub",
+                    PubSubCommand::NumPat => "numpat",
+                    PubSubCommand::ShardChannels { .. } => "shardchannels",
+                    PubSubCommand::ShardNumSub { .. } => "shardnumsub",
+                }),
+            ),
+            Command::Info(_) => ("info", None),
+            Command::Shutdown(_) => ("shutdown", None),
+            Command::Client(cmd) => (
+                "client",
+                Some(match cmd {
+                    ClientCommand::Id => "id",
+                    ClientCommand::Tracking { .. } => "tracking",
+                    ClientCommand::Caching(_) => "caching",
+                    ClientCommand::GetRedir => "getredir",
+                    ClientCommand::SetName(_) => "setname",
+                    ClientCommand::GetName => "getname",
+                }),
+            ),
+            Command::Config(cmd) => (
+                "config",
+                Some(match cmd {
+                    ConfigCommand::Get(_) => "get",
+                    ConfigCommand::Set(_) => "set",
+                    ConfigCommand::Rewrite => "rewrite",
+                    ConfigCommand::ResetStat => "resetstat",
+                }),
+            ),
+            Command::Hello(_) => ("hello", None),
+            Command::Auth(_) => ("auth", None),
+            Command::Acl(cmd) => (
+                "acl",
+                Some(match cmd {
+                    AclCommand::SetUser { .. } => "setuser",
+                    AclCommand::GetUser(_) => "getuser",
+                    AclCommand::DelUser(_) => "deluser",
+                    AclCommand::List => "list",
+                    AclCommand::WhoAmI => "whoami",
+                    AclCommand::Cat(_) => "cat",
+                    AclCommand::Log(_) | AclCommand::LogReset => "log",
+                    AclCommand::Load => "load",
+                    AclCommand::Save => "save",
+                }),
+            ),
+            Command::Unknown(cmd) => (&cmd.name, None),
+        }
+    }
+
+    // The keys the command reads or writes, checked against `~pattern`.
+    pub fn acl_keys(&self) -> Vec<(&Bytes, KeyAccess)> {
+        match self {
+            Command::Get(cmd) => vec![(&cmd.key, KeyAccess::Read)],
+            Command::Set(cmd) => vec![(&cmd.key, KeyAccess::Write)],
+            _ => vec![],
+        }
+    }
+
+    // The channels the command publishes or subscribes to, checked against
+    // `&pattern`. The flag marks PSUBSCRIBE patterns.
+    pub fn acl_channels(&self) -> Vec<(&Bytes, bool)> {
+        match self {
+            Command::Subscribe(cmd) => cmd.channels.iter().map(|c| (c, false)).collect(),
+            Command::SSubscribe(cmd) => cmd.channels.iter().map(|c| (c, false)).collect(),
+            Command::PSubscribe(cmd) => cmd.patterns.iter().map(|p| (p, true)).collect(),
+            Command::Publish(cmd) => vec![(&cmd.channel, false)],
+            Command::SPublish(cmd) => vec![(&cmd.channel, false)],
+            _ => vec![],
+        }
+    }
+}
+
 impl CommandExecutor for Unknown {
-    fn execute(self, _: &InMemStore) -> RespFrame {
+    fn execute(self, _: &Backend, _: &mut Session) -> RespFrame {
         RESP_OK.clone()
     }
 }
@@ -110,3 +380,28 @@ impl CommandExecutor for Unknown {
 fn extract_args(val: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
     Ok(val.0.into_iter().skip(start).collect::<Vec<RespFrame>>())
 }
+
+// Keys, fields, members and channels are binary safe, the payload is taken
+// as is without copying.
+fn extract_bytes(frame: RespFrame) -> Result<Bytes, CommandError> {
+    match frame {
+        RespFrame::BulkString(s) => Ok(s.into_bytes()),
+        _ => Err(CommandError::InvalidArgument(
+            "argument must be a BulkString".to_string(),
+        )),
+    }
+}
+
+// Subcommands and options are matched case-insensitively.
+fn lowercase(arg: &[u8]) -> String {
+    String::from_utf8_lossy(arg).to_ascii_lowercase()
+}
+
+fn extract_string(frame: RespFrame) -> Result<String, CommandError> {
+    match frame {
+        RespFrame::BulkString(s) => Ok(String::from_utf8(s.to_vec())?),
+        _ => Err(CommandError::InvalidArgument(
+            "argument must be a BulkString".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,474 @@
+use bytes::Bytes;
+
+use crate::{
+    backend::Backend,
+    cluster::key_hash_slot,
+    pubsub::{subscription_frame, ChannelRegistry},
+    resp::{BulkString, RespArray, RespFrame, SimpleError},
+    session::Session,
+};
+
+use super::{
+    extract_args, extract_bytes, extract_string, CommandError, CommandExecutor, PSubscribe,
+    PUnsubscribe, PubSubCommand, Publish, SPublish, SSubscribe, SUnsubscribe, Subscribe,
+    Unsubscribe,
+};
+
+impl CommandExecutor for Subscribe {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let replies = self
+            .channels
+            .into_iter()
+            .map(|channel| {
+                let count = backend.pubsub.subscribe(session, channel.clone());
+                subscription_frame("subscribe", Some(&channel), count)
+            })
+            .collect();
+        reply_each(session, replies)
+    }
+}
+
+impl CommandExecutor for Unsubscribe {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let channels = if self.channels.is_empty() {
+            session.channels.iter().cloned().collect()
+        } else {
+            self.channels
+        };
+
+        if channels.is_empty() {
+            let count = session.channels.len() + session.patterns.len();
+            return subscription_frame("unsubscribe", None, count);
+        }
+
+        let replies = channels
+            .into_iter()
+            .map(|channel| {
+                let count = backend.pubsub.unsubscribe(session, &channel);
+                subscription_frame("unsubscribe", Some(&channel), count)
+            })
+            .collect();
+        reply_each(session, replies)
+    }
+}
+
+impl CommandExecutor for PSubscribe {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let replies = self
+            .patterns
+            .into_iter()
+            .map(|pattern| {
+                let count = backend.pubsub.psubscribe(session, pattern.clone());
+                subscription_frame("psubscribe", Some(&pattern), count)
+            })
+            .collect();
+        reply_each(session, replies)
+    }
+}
+
+impl CommandExecutor for PUnsubscribe {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        let patterns = if self.patterns.is_empty() {
+            session.patterns.iter().cloned().collect()
+        } else {
+            self.patterns
+        };
+
+        if patterns.is_empty() {
+            let count = session.channels.len() + session.patterns.len();
+            return subscription_frame("punsubscribe", None, count);
+        }
+
+        let replies = patterns
+            .into_iter()
+            .map(|pattern| {
+                let count = backend.pubsub.punsubscribe(session, &pattern);
+                subscription_frame("punsubscribe", Some(&pattern), count)
+            })
+            .collect();
+        reply_each(session, replies)
+    }
+}
+
+impl CommandExecutor for Publish {
+    fn execute(self, backend: &Backend, _: &mut Session) -> RespFrame {
+        let receivers = backend.pubsub.publish(&self.channel, self.message);
+        RespFrame::Integer(receivers as i64)
+    }
+}
+
+impl CommandExecutor for SSubscribe {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        if let Some(err) = check_same_slot(&self.channels) {
+            return err;
+        }
+
+        let replies = self
+            .channels
+            .into_iter()
+            .map(|channel| {
+                let count = backend.pubsub.ssubscribe(session, channel.clone());
+                subscription_frame("ssubscribe", Some(&channel), count)
+            })
+            .collect();
+        reply_each(session, replies)
+    }
+}
+
+impl CommandExecutor for SUnsubscribe {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        if let Some(err) = check_same_slot(&self.channels) {
+            return err;
+        }
+
+        let channels = if self.channels.is_empty() {
+            // one at a time from a snapshot, so that each reply has the
+            // count left after it
+            session.shard_channels.iter().cloned().collect()
+        } else {
+            self.channels
+        };
+
+        if channels.is_empty() {
+            return subscription_frame("sunsubscribe", None, session.shard_channels.len());
+        }
+
+        let replies = channels
+            .into_iter()
+            .map(|channel| {
+                let count = backend.pubsub.sunsubscribe(session, &channel);
+                subscription_frame("sunsubscribe", Some(&channel), count)
+            })
+            .collect();
+        reply_each(session, replies)
+    }
+}
+
+impl CommandExecutor for SPublish {
+    fn execute(self, backend: &Backend, _: &mut Session) -> RespFrame {
+        let receivers = backend.pubsub.spublish(&self.channel, self.message);
+        RespFrame::Integer(receivers as i64)
+    }
+}
+
+impl CommandExecutor for PubSubCommand {
+    fn execute(self, backend: &Backend, _: &mut Session) -> RespFrame {
+        let pubsub = &backend.pubsub;
+        match self {
+            PubSubCommand::Channels { pattern } => {
+                channels_reply(&pubsub.channels, pattern.as_deref())
+            }
+            PubSubCommand::NumSub { channels } => numsub_reply(&pubsub.channels, channels),
+            PubSubCommand::NumPat => {
+                RespFrame::Integer(pubsub.patterns.channels(None).len() as i64)
+            }
+            PubSubCommand::ShardChannels { pattern } => {
+                channels_reply(&pubsub.shard, pattern.as_deref())
+            }
+            PubSubCommand::ShardNumSub { channels } => numsub_reply(&pubsub.shard, channels),
+        }
+    }
+}
+
+fn channels_reply(registry: &ChannelRegistry, pattern: Option<&[u8]>) -> RespFrame {
+    RespArray::new(
+        registry
+            .channels(pattern)
+            .into_iter()
+            .map(|c| BulkString::from(c).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+fn numsub_reply(registry: &ChannelRegistry, channels: Vec<Bytes>) -> RespFrame {
+    let mut frames = Vec::with_capacity(channels.len() * 2);
+    for channel in channels {
+        let count = registry.numsub(&channel);
+        frames.push(BulkString::from(channel).into());
+        frames.push(RespFrame::Integer(count as i64));
+    }
+    RespArray::new(frames).into()
+}
+
+impl TryFrom<RespArray> for Subscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Subscribe {
+            channels: extract_channels(value, "subscribe", true)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Unsubscribe {
+            channels: extract_channels(value, "unsubscribe", false)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PSubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PSubscribe {
+            patterns: extract_channels(value, "psubscribe", true)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PUnsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PUnsubscribe {
+            patterns: extract_channels(value, "punsubscribe", false)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Publish {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (channel, message) = extract_channel_message(value, "publish")?;
+        Ok(Publish { channel, message })
+    }
+}
+
+impl TryFrom<RespArray> for SSubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(SSubscribe {
+            channels: extract_channels(value, "ssubscribe", true)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SUnsubscribe {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(SUnsubscribe {
+            channels: extract_channels(value, "sunsubscribe", false)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SPublish {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (channel, message) = extract_channel_message(value, "spublish")?;
+        Ok(SPublish { channel, message })
+    }
+}
+
+impl TryFrom<RespArray> for PubSubCommand {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(value, 1)?.into_iter();
+        let sub = match args.next() {
+            Some(frame) => extract_string(frame)?.to_ascii_lowercase(),
+            None => return Err(CommandError::WrongNumberOfArguments("pubsub".to_string())),
+        };
+        let rest = args.map(extract_bytes).collect::<Result<Vec<_>, _>>()?;
+
+        match sub.as_str() {
+            "channels" if rest.len() <= 1 => Ok(PubSubCommand::Channels {
+                pattern: rest.into_iter().next(),
+            }),
+            "numsub" => Ok(PubSubCommand::NumSub { channels: rest }),
+            "numpat" if rest.is_empty() => Ok(PubSubCommand::NumPat),
+            "shardchannels" if rest.len() <= 1 => Ok(PubSubCommand::ShardChannels {
+                pattern: rest.into_iter().next(),
+            }),
+            "shardnumsub" => Ok(PubSubCommand::ShardNumSub { channels: rest }),
+            "channels" | "numpat" | "shardchannels" => Err(CommandError::WrongNumberOfArguments(
+                format!("pubsub|{}", sub),
+            )),
+            _ => Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'",
+                sub
+            ))),
+        }
+    }
+}
+
+fn extract_channels(
+    value: RespArray,
+    name: &str,
+    required: bool,
+) -> Result<Vec<Bytes>, CommandError> {
+    let channels = extract_args(value, 1)?
+        .into_iter()
+        .map(extract_bytes)
+        .collect::<Result<Vec<_>, _>>()?;
+    if required && channels.is_empty() {
+        return Err(CommandError::WrongNumberOfArguments(name.to_string()));
+    }
+    Ok(channels)
+}
+
+fn extract_channel_message(
+    value: RespArray,
+    name: &str,
+) -> Result<(Bytes, BulkString), CommandError> {
+    let mut args = extract_args(value, 1)?.into_iter();
+    match (args.next(), args.next(), args.next()) {
+        (Some(channel), Some(RespFrame::BulkString(message)), None) => {
+            Ok((extract_bytes(channel)?, message))
+        }
+        _ => Err(CommandError::WrongNumberOfArguments(name.to_string())),
+    }
+}
+
+// Sharded channels follow the same slot rules as keys, so all channels of a
+// single command must belong to the same slot.
+fn check_same_slot(channels: &[Bytes]) -> Option<RespFrame> {
+    let mut slots = channels.iter().map(|c| key_hash_slot(c));
+    let first = slots.next()?;
+    if slots.all(|slot| slot == first) {
+        None
+    } else {
+        Some(SimpleError::new("CROSSSLOT Keys in request don't hash to the same slot").into())
+    }
+}
+
+// (Un)subscribe commands reply once per channel: the last confirmation is
+// returned as the command's reply, the others are written before it.
+fn reply_each(session: &mut Session, mut replies: Vec<RespFrame>) -> RespFrame {
+    let last = replies.pop().expect("at least one channel");
+    for reply in replies {
+        session.reply(reply);
+    }
+    last
+}
+#[cfg(test)]
+mod tests {
+    use crate::resp::{RespNullBulkString, RespPush};
+
+    use super::*;
+
+    fn array(args: &[&str]) -> RespArray {
+        RespArray::new(
+            args.iter()
+                .map(|a| BulkString::from(*a).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+    }
+
+    #[test]
+    fn test_unsubscribe_all() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+        Subscribe::try_from(array(&["subscribe", "a", "b", "c"]))?.execute(&backend, &mut session);
+        PSubscribe::try_from(array(&["psubscribe", "p*", "q*"]))?.execute(&backend, &mut session);
+        session.take_replies();
+
+        // every reply has the number of subscriptions left after it
+        let last = Unsubscribe::try_from(array(&["unsubscribe"]))?.execute(&backend, &mut session);
+        assert_eq!(
+            session.take_replies(),
+            vec![
+                subscription_frame("unsubscribe", Some(&b"a"[..]), 4),
+                subscription_frame("unsubscribe", Some(&b"b"[..]), 3),
+            ]
+        );
+        assert_eq!(last, subscription_frame("unsubscribe", Some(&b"c"[..]), 2));
+
+        let last =
+            PUnsubscribe::try_from(array(&["punsubscribe"]))?.execute(&backend, &mut session);
+        assert_eq!(
+            session.take_replies(),
+            vec![subscription_frame("punsubscribe", Some(&b"p*"[..]), 1)]
+        );
+        assert_eq!(
+            last,
+            subscription_frame("punsubscribe", Some(&b"q*"[..]), 0)
+        );
+        assert!(!session.is_subscribed());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ssubscribe_spublish() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut s1, mut rx1) = backend.new_session();
+        let (mut s2, _rx2) = backend.new_session();
+
+        let cmd = SSubscribe::try_from(array(&["ssubscribe", "{user}:a", "{user}:b"]))?;
+        let last = cmd.execute(&backend, &mut s1);
+        assert_eq!(
+            s1.take_replies(),
+            vec![subscription_frame("ssubscribe", Some(&b"{user}:a"[..]), 1)]
+        );
+        assert_eq!(
+            last,
+            subscription_frame("ssubscribe", Some(&b"{user}:b"[..]), 2)
+        );
+
+        let cmd = SPublish::try_from(array(&["spublish", "{user}:a", "hi"]))?;
+        assert_eq!(cmd.execute(&backend, &mut s2), RespFrame::Integer(1));
+        assert_eq!(
+            rx1.try_recv()?.frame,
+            crate::pubsub::message_frame("smessage", b"{user}:a", "hi".into())
+        );
+
+        let cmd = PubSubCommand::try_from(array(&["pubsub", "shardnumsub", "{user}:a", "x"]))?;
+        assert_eq!(
+            cmd.execute(&backend, &mut s2),
+            RespArray::new(vec![
+                BulkString::from("{user}:a").into(),
+                RespFrame::Integer(1),
+                BulkString::from("x").into(),
+                RespFrame::Integer(0),
+            ])
+            .into()
+        );
+
+        let cmd = SUnsubscribe::try_from(array(&["sunsubscribe"]))?;
+        let last = cmd.execute(&backend, &mut s1);
+        assert_eq!(
+            s1.take_replies(),
+            vec![subscription_frame(
+                "sunsubscribe",
+                Some(&b"{user}:a"[..]),
+                1
+            )]
+        );
+        assert_eq!(
+            last,
+            subscription_frame("sunsubscribe", Some(&b"{user}:b"[..]), 0)
+        );
+
+        let cmd = SUnsubscribe::try_from(array(&["sunsubscribe"]))?;
+        assert_eq!(
+            cmd.execute(&backend, &mut s1),
+            RespPush::new(vec![
+                BulkString::from("sunsubscribe").into(),
+                RespNullBulkString.into(),
+                RespFrame::Integer(0),
+            ])
+            .into()
+        );
+
+        let cmd = PubSubCommand::try_from(array(&["pubsub", "shardchannels"]))?;
+        assert_eq!(
+            cmd.execute(&backend, &mut s2),
+            RespArray::new(vec![]).into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ssubscribe_cross_slot() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+
+        let cmd = SSubscribe::try_from(array(&["ssubscribe", "a", "b"]))?;
+        assert_eq!(
+            cmd.execute(&backend, &mut session),
+            SimpleError::new("CROSSSLOT Keys in request don't hash to the same slot").into()
+        );
+        assert!(!session.is_subscribed());
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt::Write;
+
+use crate::{
+    backend::Backend,
+    resp::{RespArray, RespFrame, SimpleError, VerbatimString},
+    session::Session,
+    shutdown::ShutdownFlags,
+    stats::Stats,
+};
+
+use super::{
+    extract_args, extract_bytes, extract_string, lowercase, CommandError, CommandExecutor, Info,
+    Shutdown, RESP_OK,
+};
+
+const INFO_SECTIONS: &[&str] = &["server", "clients", "memory", "stats"];
+
+impl CommandExecutor for Info {
+    fn execute(self, backend: &Backend, _: &mut Session) -> RespFrame {
+        let all = self.sections.is_empty()
+            || self
+                .sections
+                .iter()
+                .any(|s| matches!(s.as_str(), "all" | "default" | "everything"));
+
+        let mut info = String::new();
+        for section in INFO_SECTIONS {
+            if all || self.sections.iter().any(|s| s == section) {
+                if !info.is_empty() {
+                    info.push_str("\r\n");
+                }
+                write_section(&mut info, section, backend);
+            }
+        }
+        VerbatimString::txt(info).into()
+    }
+}
+
+fn write_section(info: &mut String, section: &str, backend: &Backend) {
+    let stats = &backend.stats;
+    let config = backend.config();
+    let fields: Vec<(&str, String)> = match section {
+        "server" => vec![
+            ("redis_version", "7.2.0".to_string()),
+            ("redis_mode", "standalone".to_string()),
+            ("process_id", std::process::id().to_string()),
+            ("tcp_port", config.port.to_string()),
+            (
+                "config_file",
+                config
+                    .config_file
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default(),
+            ),
+        ],
+        "clients" => vec![
+            (
+                "connected_clients",
+                Stats::get(&stats.connected_clients).to_string(),
+            ),
+            ("maxclients", config.maxclients.to_string()),
+        ],
+        "memory" => vec![("maxmemory", config.maxmemory.to_string())],
+        "stats" => vec![
+            (
+                "total_connections_received",
+                Stats::get(&stats.total_connections_received).to_string(),
+            ),
+            (
+                "rejected_connections",
+                Stats::get(&stats.rejected_connections).to_string(),
+            ),
+            (
+                "total_commands_processed",
+                Stats::get(&stats.total_commands_processed).to_string(),
+            ),
+            (
+                "pubsub_channels",
+                backend.pubsub.channels.channels(None).len().to_string(),
+            ),
+            (
+                "pubsub_patterns",
+                backend.pubsub.patterns.channels(None).len().to_string(),
+            ),
+            (
+                "pubsubshard_channels",
+                backend.pubsub.shard.channels(None).len().to_string(),
+            ),
+            (
+                "client_output_buffer_limit_disconnections",
+                Stats::get(&stats.client_output_buffer_limit_disconnections).to_string(),
+            ),
+        ],
+        _ => vec![],
+    };
+
+    let mut title = section.to_string();
+    title[..1].make_ascii_uppercase();
+    let _ = write!(info, "# {}\r\n", title);
+    for (name, value) in fields {
+        let _ = write!(info, "{}:{}\r\n", name, value);
+    }
+}
+
+impl TryFrom<RespArray> for Info {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let sections = extract_args(value, 1)?
+            .into_iter()
+            .map(|frame| extract_string(frame).map(|s| s.to_ascii_lowercase()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Info { sections })
+    }
+}
+
+// The connection is closed without a reply once the shutdown is requested,
+// see `network::handle_request`.
+impl CommandExecutor for Shutdown {
+    fn execute(self, backend: &Backend, _: &mut Session) -> RespFrame {
+        // a shutdown is never left waiting for replicas, there is nothing to abort
+        if self.abort {
+            return SimpleError::new("ERR No shutdown in progress.").into();
+        }
+        backend.shutdown.request(self.flags);
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for Shutdown {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let syntax_error = || CommandError::InvalidArgument("syntax error".to_string());
+        let mut flags = ShutdownFlags::default();
+        let mut abort = false;
+        for arg in extract_args(value, 1)? {
+            match lowercase(&extract_bytes(arg)?).as_str() {
+                "nosave" if flags.save != Some(true) => flags.save = Some(false),
+                "save" if flags.save != Some(false) => flags.save = Some(true),
+                "now" => flags.now = true,
+                "force" => flags.force = true,
+                "abort" => abort = true,
+                _ => return Err(syntax_error()),
+            }
+        }
+        if abort && flags != ShutdownFlags::default() {
+            return Err(syntax_error());
+        }
+        Ok(Shutdown { flags, abort })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, resp::BulkString};
+
+    use super::*;
+
+    #[test]
+    fn test_info_stats() -> anyhow::Result<()> {
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+
+        let cmd = Info::try_from(RespArray::new(vec![
+            BulkString::from("INFO").into(),
+            BulkString::from("Stats").into(),
+        ]))?;
+        let RespFrame::VerbatimString(info) = cmd.execute(&backend, &mut session) else {
+            panic!("INFO must reply with a verbatim string");
+        };
+        let info = String::from_utf8(info.data)?;
+        assert!(info.starts_with("# Stats\r\n"));
+        assert!(info.contains("total_connections_received:1\r\n"));
+        assert!(info.contains("client_output_buffer_limit_disconnections:0\r\n"));
+        assert!(!info.contains("# Server"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shutdown() -> anyhow::Result<()> {
+        let shutdown = |args: &[&str]| {
+            let mut frames = vec![BulkString::from("SHUTDOWN").into()];
+            frames.extend(args.iter().map(|arg| BulkString::from(*arg).into()));
+            Shutdown::try_from(RespArray::new(frames))
+        };
+        let backend = Backend::new();
+        let (mut session, _rx) = backend.new_session();
+
+        assert!(shutdown(&["SAVE", "NOSAVE"]).is_err());
+        assert!(shutdown(&["ABORT", "NOW"]).is_err());
+        assert!(shutdown(&["LATER"]).is_err());
+        assert_eq!(
+            shutdown(&["abort"])?.execute(&backend, &mut session),
+            SimpleError::new("ERR No shutdown in progress.").into()
+        );
+        assert!(!backend.shutdown.is_requested());
+
+        shutdown(&["NOSAVE", "now"])?.execute(&backend, &mut session);
+        assert_eq!(
+            backend.shutdown.flags(),
+            Some(ShutdownFlags {
+                save: Some(false),
+                now: true,
+                force: false
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_config() -> anyhow::Result<()> {
+        let config = Config::from_args(
+            ["--port", "6380", "--maxclients", "10", "--maxmemory", "1mb"].map(String::from),
+        )?;
+        let backend = Backend::with_config(config);
+        let (mut session, _rx) = backend.new_session();
+
+        let cmd = Info::try_from(RespArray::new(vec![BulkString::from("INFO").into()]))?;
+        let RespFrame::VerbatimString(info) = cmd.execute(&backend, &mut session) else {
+            panic!("INFO must reply with a verbatim string");
+        };
+        let info = String::from_utf8(info.data)?;
+        assert!(info.contains("tcp_port:6380\r\nconfig_file:\r\n"));
+        assert!(info.contains("maxclients:10\r\n"));
+        assert!(info.contains("# Memory\r\nmaxmemory:1048576\r\n"));
+        Ok(())
+    }
+}
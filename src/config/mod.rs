@@ -0,0 +1,668 @@
+mod params;
+mod rewrite;
+
+pub use params::{find_param, Param, PARAMS};
+
+use std::{
+    fmt,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use thiserror::Error;
+use tracing::level_filters::LevelFilter;
+
+use crate::{
+    pubsub::{ClientOutputBufferLimit, NotifyFlags},
+    resp::{split_args, RespLimits},
+};
+
+// Server settings, read at startup from a redis.conf style file and the
+// command line:
+//
+//     simple-redis [/path/to/redis.conf] [--directive arg ...]
+//
+// Each `--directive` takes the arguments up to the next one and is applied
+// after the file, so `--port 6380` overrides `port 6379` from redis.conf.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub config_file: Option<PathBuf>,
+    // a leading `-` marks an address as optional, `*` and `::*` are all IPv4
+    // and IPv6 addresses
+    pub bind: Vec<String>,
+    pub port: u16,
+    // listen on a Unix domain socket as well, or only there with `port 0`
+    pub unixsocket: Option<PathBuf>,
+    // octal mode of the socket file, 0 leaves it to the umask
+    pub unixsocketperm: u32,
+    // 0 disables TLS
+    pub tls_port: u16,
+    pub tls_cert_file: Option<PathBuf>,
+    pub tls_key_file: Option<PathBuf>,
+    // CA bundle client certificates are verified against
+    pub tls_ca_cert_file: Option<PathBuf>,
+    pub tls_auth_clients: TlsAuthClients,
+    // password of the default user, clients must AUTH when set
+    pub requirepass: Option<String>,
+    // users are loaded from there at startup and by ACL LOAD
+    pub aclfile: Option<PathBuf>,
+    // entries kept by ACL LOG
+    pub acllog_max_len: usize,
+    pub databases: usize,
+    pub loglevel: LogLevel,
+    // empty for stdout
+    pub logfile: Option<PathBuf>,
+    pub dir: PathBuf,
+    pub dbfilename: String,
+    // snapshot after `seconds` if at least `changes` keys changed
+    pub save: Vec<(u64, u64)>,
+    pub appendonly: bool,
+    pub appendfilename: String,
+    // 0 is unlimited
+    pub maxmemory: usize,
+    pub maxclients: usize,
+    // microseconds, negative disables it. There is no slowlog yet, the value
+    // is only stored so that it round-trips through CONFIG and redis.conf.
+    pub slowlog_log_slower_than: i64,
+    pub proto_max_bulk_len: usize,
+    // elements of an aggregate, and how deep aggregates can be nested
+    pub proto_max_multibulk_len: usize,
+    pub proto_max_nesting_depth: usize,
+    pub client_output_buffer_limit: ClientOutputBufferLimit,
+    pub notify_keyspace_events: NotifyFlags,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            config_file: None,
+            bind: vec!["0.0.0.0".to_string()],
+            port: 8088,
+            unixsocket: None,
+            unixsocketperm: 0,
+            tls_port: 0,
+            tls_cert_file: None,
+            tls_key_file: None,
+            tls_ca_cert_file: None,
+            tls_auth_clients: TlsAuthClients::Yes,
+            requirepass: None,
+            aclfile: None,
+            acllog_max_len: 128,
+            databases: 16,
+            loglevel: LogLevel::Notice,
+            logfile: None,
+            dir: PathBuf::from("."),
+            dbfilename: "dump.rdb".to_string(),
+            save: vec![(3600, 1), (300, 100), (60, 10000)],
+            appendonly: false,
+            appendfilename: "appendonly.aof".to_string(),
+            maxmemory: 0,
+            maxclients: 10000,
+            slowlog_log_slower_than: 10000,
+            proto_max_bulk_len: RespLimits::default().max_bulk_len,
+            proto_max_multibulk_len: RespLimits::default().max_multibulk_len,
+            proto_max_nesting_depth: RespLimits::default().max_depth,
+            client_output_buffer_limit: ClientOutputBufferLimit::default(),
+            notify_keyspace_events: NotifyFlags::default(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("can't read config file '{}': {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{location}: '{directive}': {message}")]
+    Invalid {
+        location: String,
+        directive: String,
+        message: String,
+    },
+    #[error("Unknown option or number of arguments for CONFIG SET - '{0}'")]
+    UnknownParam(String),
+    #[error("CONFIG SET failed (possibly related to argument '{name}') - {message}")]
+    SetFailed { name: String, message: String },
+}
+
+// `loglevel`, from the most to the least verbose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LogLevel {
+    Debug,
+    Verbose,
+    Notice,
+    Warning,
+    Nothing,
+}
+
+// The level the running server logs at, it follows CONFIG SET loglevel.
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Notice as u8);
+
+impl LogLevel {
+    const ALL: [LogLevel; 5] = [
+        LogLevel::Debug,
+        LogLevel::Verbose,
+        LogLevel::Notice,
+        LogLevel::Warning,
+        LogLevel::Nothing,
+    ];
+
+    pub fn current() -> LogLevel {
+        Self::ALL[LOG_LEVEL.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set_current(self) {
+        LOG_LEVEL.store(self as u8, Ordering::Relaxed);
+    }
+
+    pub fn level_filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Debug => LevelFilter::TRACE,
+            LogLevel::Verbose => LevelFilter::DEBUG,
+            LogLevel::Notice => LevelFilter::INFO,
+            LogLevel::Warning => LevelFilter::WARN,
+            LogLevel::Nothing => LevelFilter::OFF,
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "debug" => Ok(LogLevel::Debug),
+            "verbose" => Ok(LogLevel::Verbose),
+            "notice" => Ok(LogLevel::Notice),
+            "warning" => Ok(LogLevel::Warning),
+            "nothing" => Ok(LogLevel::Nothing),
+            _ => Err("must be one of debug, verbose, notice, warning, nothing".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Verbose => "verbose",
+            LogLevel::Notice => "notice",
+            LogLevel::Warning => "warning",
+            LogLevel::Nothing => "nothing",
+        })
+    }
+}
+
+// `tls-auth-clients`: whether TLS clients must present a certificate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsAuthClients {
+    No,
+    Yes,
+    // clients without a certificate are accepted, the others are verified
+    Optional,
+}
+
+impl FromStr for TlsAuthClients {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "no" => Ok(TlsAuthClients::No),
+            "yes" => Ok(TlsAuthClients::Yes),
+            "optional" => Ok(TlsAuthClients::Optional),
+            _ => Err("must be one of yes, no, optional".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for TlsAuthClients {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TlsAuthClients::No => "no",
+            TlsAuthClients::Yes => "yes",
+            TlsAuthClients::Optional => "optional",
+        })
+    }
+}
+
+impl Config {
+    // Arguments as passed to the server, without the program name.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, ConfigError> {
+        let mut args = args.into_iter().peekable();
+        let mut config = Config::default();
+
+        if let Some(path) = args.next_if(|arg| !arg.starts_with("--")) {
+            let io_error = |source| ConfigError::Io {
+                path: PathBuf::from(&path),
+                source,
+            };
+            // the server changes to `dir` at startup, CONFIG REWRITE must
+            // still find the file
+            let path = std::fs::canonicalize(&path).map_err(io_error)?;
+            let text = std::fs::read_to_string(&path).map_err(io_error)?;
+            config.load(&text, &path.display().to_string())?;
+            config.config_file = Some(path);
+        }
+
+        while let Some(arg) = args.next() {
+            let name = arg.strip_prefix("--").unwrap_or(&arg);
+            let mut values = vec![];
+            while let Some(value) = args.next_if(|arg| !arg.starts_with("--")) {
+                values.push(value);
+            }
+            config
+                .set(name, &values)
+                .map_err(|message| ConfigError::Invalid {
+                    location: "command line".to_string(),
+                    directive: std::iter::once(arg.as_str())
+                        .chain(values.iter().map(String::as_str))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    message,
+                })?;
+        }
+        Ok(config)
+    }
+
+    // Apply the directives of a config file, one per line. `origin` names the
+    // file in errors.
+    pub fn load(&mut self, text: &str, origin: &str) -> Result<(), ConfigError> {
+        for (i, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let invalid = |message: String| ConfigError::Invalid {
+                location: format!("{}:{}", origin, i + 1),
+                directive: trimmed.to_string(),
+                message,
+            };
+            let args = split_args(trimmed.as_bytes())
+                .ok_or_else(|| invalid("unbalanced quotes".to_string()))?
+                .into_iter()
+                .map(String::from_utf8)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid("invalid UTF-8".to_string()))?;
+            let (name, values) = args.split_first().expect("the line is not blank");
+            self.set(name, values).map_err(invalid)?;
+        }
+        Ok(())
+    }
+
+    // Set a single directive, names are case insensitive.
+    pub fn set(&mut self, name: &str, args: &[String]) -> Result<(), String> {
+        let name = name.to_ascii_lowercase();
+        match (name.as_str(), args) {
+            ("bind", [_, ..]) => {
+                for addr in args {
+                    parse_bind(addr)?;
+                }
+                self.bind = args.to_vec();
+            }
+            ("port", [port]) => {
+                self.port = port
+                    .parse()
+                    .map_err(|_| "port must be between 0 and 65535".to_string())?
+            }
+            ("unixsocket", [path]) => self.unixsocket = parse_path(path),
+            ("unixsocketperm", [mode]) => {
+                self.unixsocketperm = u32::from_str_radix(mode, 8)
+                    .ok()
+                    .filter(|mode| *mode <= 0o777)
+                    .ok_or_else(|| format!("'{}' is not a valid octal permission", mode))?;
+            }
+            ("tls-port", [port]) => {
+                self.tls_port = port
+                    .parse()
+                    .map_err(|_| "port must be between 0 and 65535".to_string())?
+            }
+            ("tls-cert-file", [path]) => self.tls_cert_file = parse_path(path),
+            ("tls-key-file", [path]) => self.tls_key_file = parse_path(path),
+            ("tls-ca-cert-file", [path]) => self.tls_ca_cert_file = parse_path(path),
+            ("tls-auth-clients", [auth]) => self.tls_auth_clients = auth.parse()?,
+            ("requirepass", [pass]) => {
+                self.requirepass = (!pass.is_empty()).then(|| pass.clone());
+            }
+            ("aclfile", [path]) => self.aclfile = parse_path(path),
+            ("acllog-max-len", [n]) => self.acllog_max_len = parse_integer(n)? as usize,
+            ("databases", [n]) => self.databases = parse_positive(n)?,
+            ("loglevel", [level]) => self.loglevel = level.parse()?,
+            ("logfile", [path]) => self.logfile = parse_path(path),
+            ("dir", [dir]) => {
+                if !Path::new(dir).is_dir() {
+                    return Err(format!("'{}' is not a directory", dir));
+                }
+                self.dir = PathBuf::from(dir);
+            }
+            ("dbfilename", [name]) => self.dbfilename = parse_filename(name)?,
+            ("save", [points]) if points.is_empty() => self.save.clear(),
+            ("save", [_, _, ..]) if args.len().is_multiple_of(2) => {
+                self.save = args
+                    .chunks(2)
+                    .map(|point| Ok((parse_integer(&point[0])?, parse_integer(&point[1])?)))
+                    .collect::<Result<_, String>>()?;
+            }
+            ("appendonly", [yes]) => self.appendonly = parse_bool(yes)?,
+            ("appendfilename", [name]) => self.appendfilename = parse_filename(name)?,
+            ("maxmemory", [size]) => self.maxmemory = parse_memory(size)?,
+            ("maxclients", [n]) => self.maxclients = parse_positive(n)?,
+            ("slowlog-log-slower-than", [n]) => {
+                self.slowlog_log_slower_than = n
+                    .parse()
+                    .map_err(|_| format!("'{}' is not an integer", n))?
+            }
+            ("proto-max-bulk-len", [size]) => {
+                let size = parse_memory(size)?;
+                if size < 1024 * 1024 {
+                    return Err("proto-max-bulk-len must be 1mb or greater".to_string());
+                }
+                self.proto_max_bulk_len = size;
+            }
+            ("proto-max-multibulk-len", [n]) => self.proto_max_multibulk_len = parse_positive(n)?,
+            ("proto-max-nesting-depth", [n]) => self.proto_max_nesting_depth = parse_positive(n)?,
+            ("client-output-buffer-limit", [_, _, _, _, ..]) if args.len().is_multiple_of(4) => {
+                for limit in args.chunks(4) {
+                    let [class, hard, soft, seconds] = limit else {
+                        unreachable!("chunks of 4");
+                    };
+                    // only pubsub clients are limited, the other classes are
+                    // accepted so that stock config files load
+                    if class.eq_ignore_ascii_case("pubsub") {
+                        self.client_output_buffer_limit = limit.join(" ").parse()?;
+                    } else if ["normal", "replica", "slave"]
+                        .iter()
+                        .any(|c| class.eq_ignore_ascii_case(c))
+                    {
+                        parse_memory(hard)?;
+                        parse_memory(soft)?;
+                        parse_integer(seconds)?;
+                    } else {
+                        return Err(format!("invalid client class '{}'", class));
+                    }
+                }
+            }
+            ("notify-keyspace-events", [flags]) => self.notify_keyspace_events = flags.parse()?,
+            _ => return Err("bad directive or wrong number of arguments".to_string()),
+        }
+        Ok(())
+    }
+
+    // Addresses to listen on, with whether binding to them may fail.
+    pub fn bind_addrs(&self) -> Vec<(IpAddr, bool)> {
+        self.bind
+            .iter()
+            .map(|addr| parse_bind(addr).expect("validated when set"))
+            .collect()
+    }
+
+    pub fn resp_limits(&self) -> RespLimits {
+        RespLimits {
+            max_bulk_len: self.proto_max_bulk_len,
+            max_multibulk_len: self.proto_max_multibulk_len,
+            max_depth: self.proto_max_nesting_depth,
+            ..RespLimits::default()
+        }
+    }
+}
+
+fn parse_bind(addr: &str) -> Result<(IpAddr, bool), String> {
+    let (addr, optional) = match addr.strip_prefix('-') {
+        Some(addr) => (addr, true),
+        None => (addr, false),
+    };
+    let ip = match addr {
+        "*" => IpAddr::from([0, 0, 0, 0]),
+        "::*" => IpAddr::from([0u16; 8]),
+        _ => addr
+            .parse()
+            .map_err(|_| format!("invalid bind address '{}'", addr))?,
+    };
+    Ok((ip, optional))
+}
+
+fn parse_integer(s: &str) -> Result<u64, String> {
+    s.parse()
+        .map_err(|_| format!("'{}' is not a non-negative integer", s))
+}
+
+fn parse_positive(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("'{}' is not a positive integer", s)),
+    }
+}
+
+fn parse_bool(s: &str) -> Result<bool, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err("argument must be 'yes' or 'no'".to_string()),
+    }
+}
+
+// An empty path unsets the option.
+fn parse_path(s: &str) -> Option<PathBuf> {
+    (!s.is_empty()).then(|| PathBuf::from(s))
+}
+
+// File names live in `dir`, they can't be paths.
+fn parse_filename(s: &str) -> Result<String, String> {
+    if s.is_empty() || s.contains('/') {
+        return Err(format!("'{}' is not a valid file name", s));
+    }
+    Ok(s.to_string())
+}
+
+// Memory sizes as accepted by redis.conf: `1024`, `1k`, `1kb`, `32mb`, `1gb`...
+// where `k`/`m`/`g` are powers of 1000 and `kb`/`mb`/`gb` powers of 1024.
+pub fn parse_memory(s: &str) -> Result<usize, String> {
+    let lower = s.to_ascii_lowercase();
+    let (num, unit) = match lower.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => lower.split_at(idx),
+        None => (lower.as_str(), ""),
+    };
+    let mul: usize = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return Err(format!("invalid memory size '{}'", s)),
+    };
+    num.parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(mul))
+        .ok_or_else(|| format!("invalid memory size '{}'", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_load_config_file() -> anyhow::Result<()> {
+        let mut config = Config::default();
+        config.load(
+            r#"
+# a comment
+BIND 127.0.0.1 -::1
+port 6380
+unixsocket /tmp/redis.sock
+unixsocketperm 770
+tls-port 6390
+tls-cert-file redis.crt
+tls-auth-clients optional
+databases 4
+loglevel WARNING
+logfile "/tmp/redis log.txt"
+save 900 1 300 10
+appendonly yes
+maxmemory 1gb
+slowlog-log-slower-than -1
+proto-max-bulk-len 1mb
+proto-max-multibulk-len 1024
+proto-max-nesting-depth 8
+client-output-buffer-limit normal 0 0 0
+client-output-buffer-limit pubsub 64mb 16mb 30
+notify-keyspace-events KEA
+"#,
+            "redis.conf",
+        )?;
+
+        assert_eq!(
+            config.bind_addrs(),
+            vec![("127.0.0.1".parse()?, false), ("::1".parse()?, true)]
+        );
+        assert_eq!(config.port, 6380);
+        assert_eq!(config.unixsocket, Some(PathBuf::from("/tmp/redis.sock")));
+        assert_eq!(config.unixsocketperm, 0o770);
+        assert_eq!(config.tls_port, 6390);
+        assert_eq!(config.tls_cert_file, Some(PathBuf::from("redis.crt")));
+        assert_eq!(config.tls_key_file, None);
+        assert_eq!(config.tls_auth_clients, TlsAuthClients::Optional);
+        assert_eq!(config.databases, 4);
+        assert_eq!(config.loglevel, LogLevel::Warning);
+        assert_eq!(config.logfile, Some(PathBuf::from("/tmp/redis log.txt")));
+        assert_eq!(config.save, vec![(900, 1), (300, 10)]);
+        assert!(config.appendonly);
+        assert_eq!(config.maxmemory, 1024 * 1024 * 1024);
+        assert_eq!(config.slowlog_log_slower_than, -1);
+        assert_eq!(
+            config.resp_limits(),
+            RespLimits {
+                max_bulk_len: 1024 * 1024,
+                max_multibulk_len: 1024,
+                max_depth: 8,
+                ..RespLimits::default()
+            }
+        );
+        assert_eq!(
+            config.client_output_buffer_limit,
+            ClientOutputBufferLimit {
+                hard: 64 * 1024 * 1024,
+                soft: 16 * 1024 * 1024,
+                soft_seconds: Duration::from_secs(30),
+            }
+        );
+        assert_eq!(config.notify_keyspace_events.to_string(), "AKE");
+
+        config.load("save \"\"\nlogfile \"\"", "redis.conf")?;
+        assert!(config.save.is_empty());
+        assert_eq!(config.logfile, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_command_line_overrides_file() -> anyhow::Result<()> {
+        let path = std::env::temp_dir()
+            .canonicalize()?
+            .join(format!("simple-redis-{}.conf", std::process::id()));
+        std::fs::write(&path, "port 6379\nmaxclients 100\n")?;
+
+        let mut cli = vec![path.display().to_string()];
+        cli.extend(args("--port 6381 --bind 127.0.0.1 ::1 --maxmemory 100mb"));
+        let config = Config::from_args(cli);
+        std::fs::remove_file(&path)?;
+        let config = config?;
+
+        assert_eq!(config.config_file, Some(path));
+        assert_eq!(config.port, 6381);
+        assert_eq!(config.maxclients, 100);
+        assert_eq!(config.bind, args("127.0.0.1 ::1"));
+        assert_eq!(config.maxmemory, 100 * 1024 * 1024);
+
+        let config = Config::from_args(vec![])?;
+        assert_eq!(config, Config::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_relative_config_path() -> anyhow::Result<()> {
+        let name = format!("simple-redis-relative-{}.conf", std::process::id());
+        std::fs::write(&name, "maxclients 100\n")?;
+        let config = Config::from_args(vec![name.clone()]);
+        let expected = std::env::current_dir()?.join(&name);
+        std::fs::remove_file(&name)?;
+
+        assert_eq!(config?.config_file, Some(expected));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let err = Config::default()
+            .load("port 6379\n\nport 70000\n", "redis.conf")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "redis.conf:3: 'port 70000': port must be between 0 and 65535"
+        );
+
+        let err = Config::from_args(args("--maxmemory 1tb")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "command line: '--maxmemory 1tb': invalid memory size '1tb'"
+        );
+
+        let err = Config::from_args(args("/no/such/redis.conf")).unwrap_err();
+        assert!(matches!(err, ConfigError::Io { .. }));
+
+        for line in [
+            "no-such-directive yes",
+            "port",
+            "port 1 2",
+            "bind localhost",
+            "unixsocketperm 800",
+            "unixsocketperm rwx",
+            "tls-auth-clients maybe",
+            "databases 0",
+            "loglevel loud",
+            "dir /no/such/dir",
+            "dbfilename ../dump.rdb",
+            "save 900",
+            "save 900 x",
+            "appendonly maybe",
+            "maxclients -1",
+            "slowlog-log-slower-than 1.5",
+            "proto-max-bulk-len 1k",
+            "proto-max-multibulk-len 0",
+            "proto-max-nesting-depth -1",
+            "client-output-buffer-limit pubsub 1mb",
+            "client-output-buffer-limit master 0 0 0",
+            "notify-keyspace-events Kq",
+            "logfile \"unbalanced",
+        ] {
+            assert!(
+                Config::default().load(line, "redis.conf").is_err(),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        assert_eq!(parse_memory("1024"), Ok(1024));
+        assert_eq!(parse_memory("1k"), Ok(1000));
+        assert_eq!(parse_memory("1KB"), Ok(1024));
+        assert_eq!(parse_memory("32mb"), Ok(32 * 1024 * 1024));
+        assert_eq!(parse_memory("2g"), Ok(2_000_000_000));
+        assert!(parse_memory("").is_err());
+        assert!(parse_memory("mb").is_err());
+        assert!(parse_memory("-1").is_err());
+        assert!(parse_memory("1.5gb").is_err());
+    }
+}
@@ -0,0 +1,378 @@
+use std::path::PathBuf;
+
+use crate::{glob::glob_match_nocase, resp::split_args};
+
+use super::{Config, ConfigError};
+
+// A parameter of CONFIG GET / SET / REWRITE. Values are read as the
+// arguments of their redis.conf directive and set through `Config::set`, so
+// they are validated the same way at startup and at runtime.
+pub struct Param {
+    pub name: &'static str,
+    // immutable parameters can only be set at startup
+    pub mutable: bool,
+    // CONFIG SET splits the value into several arguments
+    pub multiarg: bool,
+    get: fn(&Config) -> Vec<String>,
+}
+
+impl Param {
+    pub fn get(&self, config: &Config) -> Vec<String> {
+        (self.get)(config)
+    }
+}
+
+fn one(value: impl ToString) -> Vec<String> {
+    vec![value.to_string()]
+}
+
+fn path(path: &Option<PathBuf>) -> Vec<String> {
+    one(path
+        .as_ref()
+        .map_or(String::new(), |p| p.display().to_string()))
+}
+
+fn yes_no(b: bool) -> Vec<String> {
+    one(if b { "yes" } else { "no" })
+}
+
+pub const PARAMS: &[Param] = &[
+    Param {
+        name: "bind",
+        mutable: false,
+        multiarg: true,
+        get: |c| c.bind.clone(),
+    },
+    Param {
+        name: "port",
+        mutable: false,
+        multiarg: false,
+        get: |c| one(c.port),
+    },
+    Param {
+        name: "unixsocket",
+        mutable: false,
+        multiarg: false,
+        get: |c| path(&c.unixsocket),
+    },
+    Param {
+        name: "unixsocketperm",
+        mutable: false,
+        multiarg: false,
+        get: |c| one(format!("{:o}", c.unixsocketperm)),
+    },
+    Param {
+        name: "tls-port",
+        mutable: false,
+        multiarg: false,
+        get: |c| one(c.tls_port),
+    },
+    Param {
+        name: "tls-cert-file",
+        mutable: true,
+        multiarg: false,
+        get: |c| path(&c.tls_cert_file),
+    },
+    Param {
+        name: "tls-key-file",
+        mutable: true,
+        multiarg: false,
+        get: |c| path(&c.tls_key_file),
+    },
+    Param {
+        name: "tls-ca-cert-file",
+        mutable: true,
+        multiarg: false,
+        get: |c| path(&c.tls_ca_cert_file),
+    },
+    Param {
+        name: "tls-auth-clients",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.tls_auth_clients),
+    },
+    Param {
+        name: "requirepass",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.requirepass.as_deref().unwrap_or_default()),
+    },
+    Param {
+        name: "aclfile",
+        mutable: false,
+        multiarg: false,
+        get: |c| path(&c.aclfile),
+    },
+    Param {
+        name: "acllog-max-len",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.acllog_max_len),
+    },
+    Param {
+        name: "databases",
+        mutable: false,
+        multiarg: false,
+        get: |c| one(c.databases),
+    },
+    Param {
+        name: "loglevel",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.loglevel),
+    },
+    Param {
+        name: "logfile",
+        mutable: false,
+        multiarg: false,
+        get: |c| path(&c.logfile),
+    },
+    Param {
+        name: "dir",
+        mutable: false,
+        multiarg: false,
+        get: |c| one(c.dir.display()),
+    },
+    Param {
+        name: "dbfilename",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(&c.dbfilename),
+    },
+    Param {
+        name: "save",
+        mutable: true,
+        multiarg: true,
+        get: |c| match c.save.as_slice() {
+            [] => one(""),
+            points => points
+                .iter()
+                .flat_map(|(seconds, changes)| [seconds.to_string(), changes.to_string()])
+                .collect(),
+        },
+    },
+    Param {
+        name: "appendonly",
+        mutable: true,
+        multiarg: false,
+        get: |c| yes_no(c.appendonly),
+    },
+    Param {
+        name: "appendfilename",
+        mutable: false,
+        multiarg: false,
+        get: |c| one(&c.appendfilename),
+    },
+    Param {
+        name: "maxmemory",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.maxmemory),
+    },
+    Param {
+        name: "maxclients",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.maxclients),
+    },
+    Param {
+        name: "slowlog-log-slower-than",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.slowlog_log_slower_than),
+    },
+    Param {
+        name: "proto-max-bulk-len",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.proto_max_bulk_len),
+    },
+    Param {
+        name: "proto-max-multibulk-len",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.proto_max_multibulk_len),
+    },
+    Param {
+        name: "proto-max-nesting-depth",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.proto_max_nesting_depth),
+    },
+    Param {
+        name: "client-output-buffer-limit",
+        mutable: true,
+        multiarg: true,
+        get: |c| {
+            let limit = &c.client_output_buffer_limit;
+            vec![
+                "pubsub".to_string(),
+                limit.hard.to_string(),
+                limit.soft.to_string(),
+                limit.soft_seconds.as_secs().to_string(),
+            ]
+        },
+    },
+    Param {
+        name: "notify-keyspace-events",
+        mutable: true,
+        multiarg: false,
+        get: |c| one(c.notify_keyspace_events),
+    },
+];
+
+pub fn find_param(name: &str) -> Option<&'static Param> {
+    PARAMS.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+impl Config {
+    // CONFIG GET: parameters matching any of the glob patterns, with their
+    // values as a single string.
+    pub fn get_params(&self, patterns: &[&[u8]]) -> Vec<(&'static str, String)> {
+        PARAMS
+            .iter()
+            .filter(|p| {
+                patterns
+                    .iter()
+                    .any(|pat| glob_match_nocase(pat, p.name.as_bytes()))
+            })
+            .map(|p| (p.name, p.get(self).join(" ")))
+            .collect()
+    }
+
+    // CONFIG SET: all parameters are set, or none of them.
+    pub fn set_params(&mut self, params: &[(String, String)]) -> Result<(), ConfigError> {
+        let mut updated = self.clone();
+        for (i, (name, value)) in params.iter().enumerate() {
+            let failed = |message: &str| ConfigError::SetFailed {
+                name: name.clone(),
+                message: message.to_string(),
+            };
+            let param = find_param(name).ok_or_else(|| ConfigError::UnknownParam(name.clone()))?;
+            if !param.mutable {
+                return Err(failed("can't set immutable config"));
+            }
+            if params[..i]
+                .iter()
+                .any(|(n, _)| n.eq_ignore_ascii_case(name))
+            {
+                return Err(failed("duplicate parameter"));
+            }
+
+            let mut args = if param.multiarg {
+                split_args(value.as_bytes())
+                    .ok_or_else(|| failed("unbalanced quotes"))?
+                    .into_iter()
+                    .map(String::from_utf8)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| failed("invalid UTF-8"))?
+            } else {
+                vec![value.clone()]
+            };
+            if args.is_empty() {
+                // e.g. `save ""`
+                args.push(String::new());
+            }
+            updated
+                .set(param.name, &args)
+                .map_err(|message| failed(&message))?;
+        }
+        *self = updated;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(config: &mut Config, params: &[(&str, &str)]) -> Result<(), ConfigError> {
+        let params: Vec<_> = params
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.to_string()))
+            .collect();
+        config.set_params(&params)
+    }
+
+    #[test]
+    fn test_get_params() {
+        let config = Config::default();
+        assert_eq!(
+            config.get_params(&[b"MAXMEMORY", b"maxclients"]),
+            vec![
+                ("maxmemory", "0".to_string()),
+                ("maxclients", "10000".to_string())
+            ]
+        );
+        assert_eq!(
+            config.get_params(&[b"*limit*"]),
+            vec![(
+                "client-output-buffer-limit",
+                "pubsub 33554432 8388608 60".to_string()
+            )]
+        );
+        assert_eq!(
+            config.get_params(&[b"save", b"bind"]),
+            vec![
+                ("bind", "0.0.0.0".to_string()),
+                ("save", "3600 1 300 100 60 10000".to_string())
+            ]
+        );
+        assert_eq!(config.get_params(&[b"*"]).len(), PARAMS.len());
+        assert!(config.get_params(&[b"nosuchparam"]).is_empty());
+    }
+
+    #[test]
+    fn test_set_params() -> anyhow::Result<()> {
+        let mut config = Config::default();
+        set(
+            &mut config,
+            &[
+                ("maxmemory", "1gb"),
+                ("slowlog-log-slower-than", "0"),
+                ("SAVE", "900 1"),
+                ("notify-keyspace-events", "KEA"),
+                (
+                    "client-output-buffer-limit",
+                    "normal 0 0 0 pubsub 1mb 512kb 10",
+                ),
+            ],
+        )?;
+        assert_eq!(config.maxmemory, 1024 * 1024 * 1024);
+        assert_eq!(config.slowlog_log_slower_than, 0);
+        assert_eq!(config.save, vec![(900, 1)]);
+        assert_eq!(config.notify_keyspace_events.to_string(), "AKE");
+        assert_eq!(config.client_output_buffer_limit.soft, 512 * 1024);
+
+        set(&mut config, &[("save", "")])?;
+        assert!(config.save.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_params_is_atomic() {
+        let mut config = Config::default();
+        for (params, expected) in [
+            (
+                &[("maxmemory", "1gb"), ("maxclients", "x")][..],
+                "CONFIG SET failed (possibly related to argument 'maxclients') - 'x' is not a positive integer",
+            ),
+            (
+                &[("maxmemory", "1gb"), ("port", "6380")],
+                "CONFIG SET failed (possibly related to argument 'port') - can't set immutable config",
+            ),
+            (
+                &[("maxmemory", "1gb"), ("MAXMEMORY", "2gb")],
+                "CONFIG SET failed (possibly related to argument 'MAXMEMORY') - duplicate parameter",
+            ),
+            (
+                &[("maxmemory", "1gb"), ("nosuchparam", "1")],
+                "Unknown option or number of arguments for CONFIG SET - 'nosuchparam'",
+            ),
+        ] {
+            let err = set(&mut config, params).unwrap_err();
+            assert_eq!(err.to_string(), expected);
+        }
+        assert_eq!(config, Config::default());
+    }
+}
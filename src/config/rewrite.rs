@@ -0,0 +1,186 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use crate::resp::split_args;
+
+use super::{
+    params::{Param, PARAMS},
+    Config,
+};
+
+const GENERATED: &str = "# Generated by CONFIG REWRITE";
+
+impl Config {
+    // CONFIG REWRITE: write the running configuration back to `path`. The new
+    // file replaces the old one at once, it is never left half written.
+    pub fn rewrite(&self, path: &Path) -> io::Result<()> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(format!(".rewrite-{}", std::process::id()));
+        fs::write(&tmp, self.rewrite_text(&text))?;
+        fs::rename(&tmp, path)
+    }
+
+    // The first line setting a parameter is replaced with its current value
+    // and the others are dropped. Parameters the file doesn't set are added
+    // at the end when they differ from their defaults. Comments, blank lines
+    // and anything else stay as they are.
+    fn rewrite_text(&self, text: &str) -> String {
+        let mut written = vec![false; PARAMS.len()];
+        let mut lines: Vec<String> = vec![];
+        for line in text.lines() {
+            let args = split_args(line.as_bytes()).unwrap_or_default();
+            let name = args.first().map(|name| String::from_utf8_lossy(name));
+            let Some(i) = name.and_then(|name| {
+                PARAMS
+                    .iter()
+                    .position(|p| p.name.eq_ignore_ascii_case(&name))
+            }) else {
+                lines.push(line.to_string());
+                continue;
+            };
+            // only the pubsub class is known, limits of other classes are kept
+            if PARAMS[i].name == "client-output-buffer-limit"
+                && !args
+                    .get(1)
+                    .is_some_and(|c| c.eq_ignore_ascii_case(b"pubsub"))
+            {
+                lines.push(line.to_string());
+                continue;
+            }
+            if !written[i] {
+                lines.push(self.directive(&PARAMS[i]));
+                written[i] = true;
+            }
+        }
+
+        let default = Config::default();
+        let missing: Vec<_> = PARAMS
+            .iter()
+            .zip(written)
+            .filter(|(p, written)| !written && p.get(self) != p.get(&default))
+            .map(|(p, _)| self.directive(p))
+            .collect();
+        if !missing.is_empty() && !lines.iter().any(|line| line == GENERATED) {
+            lines.push(GENERATED.to_string());
+        }
+        lines.extend(missing);
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+        text
+    }
+
+    fn directive(&self, param: &Param) -> String {
+        let mut line = param.name.to_string();
+        for arg in param.get(self) {
+            line.push(' ');
+            line.push_str(&quote(&arg));
+        }
+        line
+    }
+}
+
+// Quote an argument unless `split_args` reads it back as is.
+fn quote(arg: &str) -> String {
+    let plain = |b: u8| !b.is_ascii() || (b.is_ascii_graphic() && b != b'"' && b != b'\'');
+    if !arg.is_empty() && arg.bytes().all(plain) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\x07' => quoted.push_str("\\a"),
+            '\x08' => quoted.push_str("\\b"),
+            c if c.is_ascii_control() => {
+                let _ = write!(quoted, "\\x{:02x}", c as u8);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_rewrite_text() {
+        let text = "\
+# Redis configuration
+
+port 8088
+# memory
+maxmemory 100mb
+client-output-buffer-limit normal 0 0 0
+client-output-buffer-limit pubsub 32mb 8mb 60
+save 900 1
+save 300 10
+tcp-keepalive 300
+";
+        // the unknown directive is left alone
+        let config = Config {
+            maxmemory: 2 * 1024 * 1024,
+            save: vec![],
+            logfile: Some(PathBuf::from("/var/log/redis \"main\".log")),
+            maxclients: 10,
+            ..Default::default()
+        };
+
+        let expected = "\
+# Redis configuration
+
+port 8088
+# memory
+maxmemory 2097152
+client-output-buffer-limit normal 0 0 0
+client-output-buffer-limit pubsub 33554432 8388608 60
+save \"\"
+tcp-keepalive 300
+# Generated by CONFIG REWRITE
+logfile \"/var/log/redis \\\"main\\\".log\"
+maxclients 10
+";
+        let rewritten = config.rewrite_text(text);
+        assert_eq!(rewritten, expected);
+        // nothing changes the second time
+        assert_eq!(config.rewrite_text(&rewritten), expected);
+    }
+
+    #[test]
+    fn test_rewrite_round_trip() -> anyhow::Result<()> {
+        let mut config = Config::default();
+        config.set_params(&[
+            ("dbfilename".to_string(), "dump\t1.rdb".to_string()),
+            ("notify-keyspace-events".to_string(), "Kx".to_string()),
+            ("loglevel".to_string(), "debug".to_string()),
+            ("save".to_string(), "60 5 10 1000".to_string()),
+        ])?;
+        config.bind = vec!["127.0.0.1".to_string(), "-::1".to_string()];
+
+        let path =
+            std::env::temp_dir().join(format!("simple-redis-rewrite-{}.conf", std::process::id()));
+        let rewritten = config
+            .rewrite(&path)
+            .and_then(|_| fs::read_to_string(&path));
+        fs::remove_file(&path)?;
+
+        let mut loaded = Config::default();
+        loaded.load(&rewritten?, "redis.conf")?;
+        assert_eq!(loaded, config);
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+// Glob-style pattern matching with the same semantics as Redis' `stringmatchlen`:
+// `*` matches any sequence, `?` matches one byte, `[...]` matches a class
+// (with `^` negation and `a-z` ranges) and `\` escapes the next byte.
+pub fn glob_match(pattern: &[u8], s: &[u8]) -> bool {
+    glob_match_impl(pattern, s, false)
+}
+
+pub fn glob_match_nocase(pattern: &[u8], s: &[u8]) -> bool {
+    glob_match_impl(pattern, s, true)
+}
+
+fn glob_match_impl(mut p: &[u8], mut s: &[u8], nocase: bool) -> bool {
+    let eq = |a: u8, b: u8| {
+        if nocase {
+            a.eq_ignore_ascii_case(&b)
+        } else {
+            a == b
+        }
+    };
+
+    while !p.is_empty() {
+        match p[0] {
+            b'*' => {
+                while p.len() > 1 && p[1] == b'*' {
+                    p = &p[1..];
+                }
+                if p.len() == 1 {
+                    return true;
+                }
+                for i in 0..=s.len() {
+                    if glob_match_impl(&p[1..], &s[i..], nocase) {
+                        return true;
+                    }
+                }
+                return false;
+            }
+            b'?' => {
+                if s.is_empty() {
+                    return false;
+                }
+                s = &s[1..];
+            }
+            b'[' => {
+                if s.is_empty() {
+                    return false;
+                }
+                p = &p[1..];
+                let not = p.first() == Some(&b'^');
+                if not {
+                    p = &p[1..];
+                }
+                let mut matched = false;
+                loop {
+                    match p {
+                        [] => break,
+                        [b']', ..] => break,
+                        [b'\\', c, rest @ ..] => {
+                            if eq(*c, s[0]) {
+                                matched = true;
+                            }
+                            p = rest;
+                        }
+                        [start, b'-', end, rest @ ..] if *end != b']' => {
+                            let (lo, hi) = if start <= end {
+                                (*start, *end)
+                            } else {
+                                (*end, *start)
+                            };
+                            let c = s[0];
+                            if nocase {
+                                let c = c.to_ascii_lowercase();
+                                let (lo, hi) = (lo.to_ascii_lowercase(), hi.to_ascii_lowercase());
+                                if c >= lo && c <= hi {
+                                    matched = true;
+                                }
+                            } else if c >= lo && c <= hi {
+                                matched = true;
+                            }
+                            p = rest;
+                        }
+                        [c, rest @ ..] => {
+                            if eq(*c, s[0]) {
+                                matched = true;
+                            }
+                            p = rest;
+                        }
+                    }
+                }
+                if not {
+                    matched = !matched;
+                }
+                if !matched {
+                    return false;
+                }
+                s = &s[1..];
+            }
+            b'\\' if p.len() >= 2 => {
+                if s.is_empty() || !eq(p[1], s[0]) {
+                    return false;
+                }
+                p = &p[1..];
+                s = &s[1..];
+            }
+            c => {
+                if s.is_empty() || !eq(c, s[0]) {
+                    return false;
+                }
+                s = &s[1..];
+            }
+        }
+        if !p.is_empty() {
+            p = &p[1..];
+        }
+    }
+    s.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(b"*", b"anything"));
+        assert!(glob_match(b"news.*", b"news.sport"));
+        assert!(!glob_match(b"news.*", b"weather"));
+        assert!(glob_match(b"h?llo", b"hello"));
+        assert!(glob_match(b"h[ae]llo", b"hallo"));
+        assert!(!glob_match(b"h[^e]llo", b"hello"));
+        assert!(glob_match(b"h[a-b]llo", b"hbllo"));
+        assert!(glob_match(b"h\\*llo", b"h*llo"));
+        assert!(!glob_match(b"h\\*llo", b"hello"));
+        assert!(glob_match_nocase(b"MAX*", b"maxmemory"));
+    }
+}
@@ -1,4 +1,16 @@
+pub mod acl;
+pub mod auth;
+pub mod backend;
+pub mod cluster;
 pub mod command;
+pub mod config;
+pub mod glob;
 pub mod network;
+pub mod pubsub;
 pub mod resp;
+pub mod session;
+pub mod shutdown;
+pub mod stats;
 pub mod storage;
+pub mod tls;
+pub mod tracking;
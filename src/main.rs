@@ -1,31 +1,282 @@
-use simple_redis::{network, storage::memory::InMemStore};
-use tokio::net::TcpListener;
-use tracing::{info, warn};
+use std::{
+    fs::{self, OpenOptions, Permissions},
+    future::{ready, Future},
+    net::SocketAddr,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use anyhow::Context;
+use simple_redis::{
+    backend::Backend,
+    config::{Config, LogLevel},
+    network,
+    shutdown::ShutdownFlags,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, UnixListener},
+    signal::unix::{signal, SignalKind},
+    task::JoinSet,
+    time,
+};
+use tokio_util::task::TaskTracker;
+use tracing::{error, info, warn};
+use tracing_subscriber::{
+    filter::dynamic_filter_fn, fmt::writer::BoxMakeWriter, layer::SubscriberExt,
+    util::SubscriberInitExt, EnvFilter, Layer,
+};
+
+// How long connections get to finish their current command on shutdown.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("*** FATAL CONFIG ERROR ***\n{}", e);
+            std::process::exit(1);
+        }
+    };
+    // a relative logfile is in `dir`, like the persistence files
+    std::env::set_current_dir(&config.dir)
+        .with_context(|| format!("can't chdir to '{}'", config.dir.display()))?;
+    init_logging(&config)?;
+
+    let mut listeners = vec![];
+    // port 0 disables TCP
+    if config.port != 0 {
+        for listener in bind_tcp(&config, config.port).await? {
+            listeners.push(Listener::Tcp(listener));
+        }
+    }
+    if config.tls_port != 0 {
+        for listener in bind_tcp(&config, config.tls_port).await? {
+            listeners.push(Listener::Tls(listener));
+        }
+    }
+    if let Some(path) = &config.unixsocket {
+        let listener = bind_unix(path, config.unixsocketperm)
+            .with_context(|| format!("can't listen on unix socket '{}'", path.display()))?;
+        info!("listen on unix socket: {}", path.display());
+        listeners.push(Listener::Unix(listener, path.clone()));
+    }
+    anyhow::ensure!(!listeners.is_empty(), "no address to listen on");
+
+    let backend = Backend::with_config(config);
+    backend
+        .tls
+        .reload(&backend.config())
+        .context("can't configure TLS")?;
+    let aclfile = backend.config().aclfile.clone();
+    if let Some(path) = aclfile {
+        backend.acl.load(&path).context("can't load users")?;
+    }
+    tokio::spawn(watch_signals(backend.clone()));
+
+    let connections = TaskTracker::new();
+    let mut accepts = JoinSet::new();
+    for listener in listeners {
+        accepts.spawn(accept_loop(listener, backend.clone(), connections.clone()));
+    }
+    // listeners stop on shutdown, or if accepting fails
+    let mut ret = Ok(());
+    while let Some(accept) = accepts.join_next().await {
+        if let Err(e) = accept.map_err(anyhow::Error::from).and_then(|r| r) {
+            error!("accept error: {}", e);
+            backend.shutdown.request(ShutdownFlags::default());
+            ret = Err(e);
+        }
+    }
 
-    let addr = "0.0.0.0:8088";
-    info!("listen on: {}", addr);
-    let listener = TcpListener::bind(addr).await?;
+    info!(
+        "shutting down, waiting for {} connections",
+        connections.len()
+    );
+    connections.close();
+    // a client that stopped reading would keep its connection writing
+    // forever, the tasks left are dropped when the runtime stops
+    if time::timeout(SHUTDOWN_GRACE_PERIOD, connections.wait())
+        .await
+        .is_err()
+    {
+        warn!(
+            "{} connections still busy after {:?}, closing them",
+            connections.len(),
+            SHUTDOWN_GRACE_PERIOD
+        );
+    }
+    if let Some(path) = &backend.config().unixsocket {
+        if let Err(e) = fs::remove_file(path) {
+            warn!("can't remove unix socket '{}': {}", path.display(), e);
+        }
+    }
+
+    let flags = backend.shutdown.flags().unwrap_or_default();
+    if flags.save.unwrap_or(!backend.config().save.is_empty()) {
+        warn!("persistence is not supported, there is no snapshot to save");
+    }
+    info!("ready to exit, bye bye");
+    ret
+}
 
-    let store = InMemStore::new();
+// The first SIGTERM or SIGINT shuts the server down gracefully, another one
+// while connections are closing exits right away.
+async fn watch_signals(backend: Backend) -> anyhow::Result<()> {
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let mut sigint = signal(SignalKind::interrupt())?;
     loop {
-        let (stream, raddr) = listener.accept().await?;
-        info!("accept connection from: {}", raddr);
+        let name = tokio::select! {
+            _ = sigterm.recv() => "SIGTERM",
+            _ = sigint.recv() => "SIGINT",
+        };
+        if !backend.shutdown.request(ShutdownFlags::default()) {
+            warn!("received {} during shutdown, exiting now", name);
+            std::process::exit(1);
+        }
+        warn!("received {}, scheduling shutdown", name);
+    }
+}
 
-        let inner_store = store.clone();
+// `RUST_LOG` takes precedence when set, otherwise events are filtered by
+// `loglevel`, which can be changed at runtime with CONFIG SET.
+fn init_logging(config: &Config) -> anyhow::Result<()> {
+    config.loglevel.set_current();
+    let env_filter =
+        std::env::var_os(EnvFilter::DEFAULT_ENV).map(|_| EnvFilter::from_default_env());
+    let use_loglevel = env_filter.is_none();
+    let loglevel = dynamic_filter_fn(move |meta, _| {
+        !use_loglevel || *meta.level() <= LogLevel::current().level_filter()
+    });
+
+    let writer = match &config.logfile {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("can't open log file '{}'", path.display()))?;
+            BoxMakeWriter::new(Mutex::new(file))
+        }
+        None => BoxMakeWriter::new(std::io::stdout),
+    };
+    let layer = tracing_subscriber::fmt::layer()
+        .with_ansi(config.logfile.is_none())
+        .with_writer(writer)
+        .with_filter(loglevel);
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(layer)
+        .init();
+    Ok(())
+}
 
-        tokio::spawn(async move {
-            match network::stream_handler(stream, inner_store).await {
-                Ok(_) => {
-                    info!("connection from {} exit", raddr);
+enum Listener {
+    Tcp(TcpListener),
+    // the handshake is done by the connection's task
+    Tls(TcpListener),
+    // with the socket path, to name the clients
+    Unix(UnixListener, PathBuf),
+}
+
+async fn bind_tcp(config: &Config, port: u16) -> anyhow::Result<Vec<TcpListener>> {
+    let mut listeners = vec![];
+    for (ip, optional) in config.bind_addrs() {
+        let addr = SocketAddr::new(ip, port);
+        match TcpListener::bind(addr).await {
+            Ok(listener) => {
+                info!("listen on: {}", addr);
+                listeners.push(listener);
+            }
+            Err(e) if optional => warn!("skipping optional address {}: {}", addr, e),
+            Err(e) => return Err(e).with_context(|| format!("can't listen on {}", addr)),
+        }
+    }
+    Ok(listeners)
+}
+
+// A socket file left by a server that didn't shut down cleanly would make
+// binding fail, so it is removed first, like Redis does.
+fn bind_unix(path: &Path, perm: u32) -> std::io::Result<UnixListener> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let listener = UnixListener::bind(path)?;
+    if perm != 0 {
+        fs::set_permissions(path, Permissions::from_mode(perm))?;
+    }
+    Ok(listener)
+}
+
+async fn accept_loop(
+    listener: Listener,
+    backend: Backend,
+    connections: TaskTracker,
+) -> anyhow::Result<()> {
+    loop {
+        let shutdown = backend.shutdown.requested();
+        match &listener {
+            Listener::Tcp(listener) => tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, raddr) = accepted?;
+                    spawn_connection(ready(Ok(stream)), raddr.to_string(), &backend, &connections);
                 }
-                Err(e) => {
-                    warn!("handle error for {}:{}", raddr, e);
+                _ = shutdown => return Ok(()),
+            },
+            Listener::Tls(listener) => tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, raddr) = accepted?;
+                    // certificates may have been reloaded since the last one
+                    let Some(acceptor) = backend.tls.acceptor() else {
+                        warn!("TLS is not configured, dropping connection from {}", raddr);
+                        continue;
+                    };
+                    spawn_connection(acceptor.accept(stream), raddr.to_string(), &backend, &connections);
                 }
-            }
-        });
+                _ = shutdown => return Ok(()),
+            },
+            Listener::Unix(listener, path) => tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, _) = accepted?;
+                    spawn_connection(ready(Ok(stream)), path.display().to_string(), &backend, &connections);
+                }
+                _ = shutdown => return Ok(()),
+            },
+        }
     }
 }
+
+// `connect` resolves to the client's stream once it is ready to be served,
+// e.g. after the TLS handshake.
+fn spawn_connection<F, S>(connect: F, raddr: String, backend: &Backend, connections: &TaskTracker)
+where
+    F: Future<Output = std::io::Result<S>> + Send + 'static,
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    info!("accept connection from: {}", raddr);
+
+    let backend = backend.clone();
+
+    connections.spawn(async move {
+        let stream = tokio::select! {
+            stream = connect => stream,
+            _ = backend.shutdown.requested() => return,
+        };
+        let ret = match stream {
+            Ok(stream) => network::stream_handler(stream, backend).await,
+            Err(e) => Err(e.into()),
+        };
+        match ret {
+            Ok(_) => {
+                info!("connection from {} exit", raddr);
+            }
+            Err(e) => {
+                warn!("handle error for {}:{}", raddr, e);
+            }
+        }
+    });
+}
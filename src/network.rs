@@ -1,67 +1,379 @@
+use std::io;
+
 use anyhow::Result;
-use bytes::BytesMut;
-use futures::SinkExt;
-use tokio::net::TcpStream;
+use bytes::{Buf, BytesMut};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc::UnboundedReceiver;
 use tokio_stream::StreamExt;
-use tokio_util::codec::{Decoder, Encoder, Framed};
-use tracing::info;
+use tokio_util::codec::{Decoder, Encoder, FramedRead};
+use tokio_util::io::poll_write_buf;
+use tracing::{info, warn};
 
 use crate::{
-    command::{Command, CommandExecutor},
-    resp::{RespDecode, RespEncode, RespError, RespFrame},
-    storage::memory::InMemStore,
+    acl::{Denied, DenyReason},
+    backend::Backend,
+    command::{connection::client_info, ClientCommand, Command, CommandExecutor},
+    resp::{
+        parse_inline, RespEncode, RespError, RespFrame, RespLimits, RespNullBulkString, RespOutput,
+        RespParser, SimpleError,
+    },
+    session::{QueuedFrame, Session},
+    stats::Stats,
 };
 
+// Frames RESP values on a byte stream. Push frames (`>`) are out-of-band
+// data, they are decoded like any other frame and it is up to the consumer
+// to keep them out of request/response pairing, see `RespFrame::is_push`.
+//
+// Decoding is incremental, a frame split across reads is picked up where the
+// previous read left off instead of being parsed again from the start. Input
+// that doesn't start with a RESP type byte is an inline command, which is
+// turned into the array of bulk strings a client would have sent.
+//
+// Frames are encoded for the peer's protocol version: RESP3 types are
+// downgraded to their RESP2 counterparts unless RESP3 was negotiated.
+#[derive(Debug)]
+pub struct RespFrameCodec {
+    protocol: u8,
+    parser: RespParser,
+}
+
+impl RespFrameCodec {
+    pub fn new(protocol: u8) -> Self {
+        Self {
+            protocol,
+            parser: RespParser::new(),
+        }
+    }
+
+    pub fn set_protocol(&mut self, protocol: u8) {
+        self.protocol = protocol;
+    }
+
+    pub fn set_limits(&mut self, limits: RespLimits) {
+        self.parser.set_limits(limits);
+    }
+}
+
+impl Default for RespFrameCodec {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+// Writes frames to the peer in its protocol version. Unlike the codec's encoder
+// it doesn't copy large bulk payloads into the write buffer, they are written
+// straight from the frames with vectored writes.
 #[derive(Debug)]
-struct RespFrameCodec;
+struct RespWriter<W> {
+    inner: W,
+    protocol: u8,
+    output: RespOutput,
+}
+
+impl<W: AsyncWrite + Unpin> RespWriter<W> {
+    fn new(inner: W, protocol: u8) -> Self {
+        Self {
+            inner,
+            protocol,
+            output: RespOutput::new(),
+        }
+    }
+
+    fn set_protocol(&mut self, protocol: u8) {
+        self.protocol = protocol;
+    }
+
+    // Queue a frame to be written by the next `flush`.
+    fn feed(&mut self, frame: RespFrame) {
+        if self.protocol < 3 {
+            self.output.push(&frame.into_resp2());
+        } else {
+            self.output.push(&frame);
+        }
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        while self.output.has_remaining() {
+            let n = std::future::poll_fn(|cx| {
+                poll_write_buf(std::pin::Pin::new(&mut self.inner), cx, &mut self.output)
+            })
+            .await?;
+            if n == 0 {
+                return Err(io::ErrorKind::WriteZero.into());
+            }
+        }
+        self.inner.flush().await
+    }
+
+    async fn close(&mut self) -> io::Result<()> {
+        self.inner.shutdown().await
+    }
+
+    async fn send(&mut self, frame: RespFrame) -> io::Result<()> {
+        self.feed(frame);
+        self.flush().await
+    }
+}
 
 #[derive(Debug)]
 struct RedisRequest {
     frame: RespFrame,
-    store: InMemStore,
+    backend: Backend,
 }
 
 #[derive(Debug)]
 struct RedisResponse {
     frame: RespFrame,
+    // close the connection instead of replying
+    close: bool,
 }
 
-pub async fn stream_handler(stream: TcpStream, store: InMemStore) -> Result<()> {
-    let mut framed = Framed::new(stream, RespFrameCodec);
+// Serve a client connection, over TCP, a Unix socket or anything else that
+// carries a byte stream.
+pub async fn stream_handler<S>(mut stream: S, backend: Backend) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    if Stats::get(&backend.stats.connected_clients) >= backend.config().maxclients as u64 {
+        Stats::incr(&backend.stats.rejected_connections);
+        stream
+            .write_all(b"-ERR max number of clients reached\r\n")
+            .await?;
+        return Ok(());
+    }
+
+    let (mut session, mut messages) = backend.new_session();
+    let (reader, writer) = tokio::io::split(stream);
+    let mut codec = RespFrameCodec::new(session.protocol());
+    codec.set_limits(backend.config().resp_limits());
+    let mut reader = FramedRead::new(reader, codec);
+    let mut writer = RespWriter::new(writer, session.protocol());
+    let handle = session.handle().clone();
 
+    // the connection can be closed from elsewhere, e.g. when a subscriber
+    // can't keep up with its output buffer limit
+    let ret = tokio::select! {
+        ret = serve(&mut reader, &mut writer, &backend, &mut session, &mut messages) => ret,
+        _ = handle.closed() => {
+            warn!("closing client {}: {:?}", handle.id(), handle.close_reason());
+            Ok(())
+        }
+    };
+    backend.close_session(&mut session);
+    if ret.is_ok() {
+        // the peer may already be gone
+        let _ = writer.close().await;
+    }
+    ret
+}
+
+async fn serve<R, W>(
+    reader: &mut FramedRead<R, RespFrameCodec>,
+    writer: &mut RespWriter<W>,
+    backend: &Backend,
+    session: &mut Session,
+    messages: &mut UnboundedReceiver<QueuedFrame>,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
     loop {
-        match framed.next().await {
-            Some(Ok(frame)) => {
-                info!("received frame: {:?}", frame);
-                let request = RedisRequest {
-                    frame,
-                    store: store.clone(),
-                };
-
-                let response = handle_request(request).await?;
-
-                info!("sending response: {:?}", response.frame);
-                framed.send(response.frame).await?;
+        tokio::select! {
+            frame = reader.next() => match frame {
+                // pushes never expect a reply, so they can't be requests
+                Some(Ok(RespFrame::Push(push))) => {
+                    warn!("ignoring push frame from client {}: {:?}", session.id(), push);
+                }
+                Some(Ok(frame)) => {
+                    if carries_password(&frame) {
+                        info!("received frame: (redacted)");
+                    } else {
+                        info!("received frame:\n{}", frame.pretty());
+                    }
+                    let request = RedisRequest {
+                        frame,
+                        backend: backend.clone(),
+                    };
+
+                    let response = handle_request(request, session).await?;
+                    reader.decoder_mut().set_protocol(session.protocol());
+                    // the proto-max-* limits may have changed with CONFIG SET
+                    reader.decoder_mut().set_limits(backend.config().resp_limits());
+                    writer.set_protocol(session.protocol());
+
+                    for reply in session.take_replies() {
+                        writer.feed(reply);
+                    }
+                    if response.close {
+                        writer.flush().await?;
+                        return Ok(());
+                    }
+                    info!("sending response:\n{}", response.frame.pretty());
+                    writer.send(response.frame).await?;
+                }
+                Some(Err(e)) => {
+                    // tell the client what was wrong with its request before
+                    // hanging up, like Redis does for protocol errors
+                    if let Some(err) = e.downcast_ref::<RespError>() {
+                        writer.send(SimpleError::new(format!("ERR {}", err)).into()).await?;
+                    }
+                    return Err(e);
+                }
+                None => return Ok(()),
+            },
+            Some(message) = messages.recv() => {
+                writer.send(message.frame).await?;
+                session.handle().sent(message.size);
             }
-            Some(Err(e)) => return Err(e),
-            None => return Ok(()),
+            // the command in progress, if any, has been answered
+            _ = backend.shutdown.requested() => return Ok(()),
+        }
+    }
+}
+
+// AUTH, HELLO ... AUTH, ACL SETUSER and CONFIG SET, which may set
+// requirepass, are kept out of the logs.
+fn carries_password(frame: &RespFrame) -> bool {
+    let RespFrame::Array(args) = frame else {
+        return false;
+    };
+    let arg = |i: usize| match args.get(i) {
+        Some(RespFrame::BulkString(arg)) => Some(arg.as_ref()),
+        _ => None,
+    };
+    match arg(0) {
+        Some(cmd) if cmd.eq_ignore_ascii_case(b"auth") || cmd.eq_ignore_ascii_case(b"hello") => {
+            true
         }
+        Some(cmd) if cmd.eq_ignore_ascii_case(b"acl") => {
+            arg(1).is_some_and(|sub| sub.eq_ignore_ascii_case(b"setuser"))
+        }
+        Some(cmd) if cmd.eq_ignore_ascii_case(b"config") => {
+            arg(1).is_some_and(|sub| sub.eq_ignore_ascii_case(b"set"))
+        }
+        _ => false,
     }
 }
 
-async fn handle_request(request: RedisRequest) -> Result<RedisResponse> {
-    let (frame, store) = (request.frame, request.store);
-    let cmd = Command::try_from(frame)?;
+async fn handle_request(request: RedisRequest, session: &mut Session) -> Result<RedisResponse> {
+    let (frame, backend) = (request.frame, request.backend);
+    let cmd = match Command::try_from(frame) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            return Ok(RedisResponse {
+                frame: SimpleError::new(format!("ERR {}", e)).into(),
+                close: false,
+            })
+        }
+    };
     info!("Execute command: {:?}", cmd);
+    Stats::incr(&backend.stats.total_commands_processed);
+
+    if !session.is_authenticated() && !cmd.allowed_unauthenticated() {
+        return Ok(RedisResponse {
+            frame: SimpleError::new("NOAUTH Authentication required.").into(),
+            close: false,
+        });
+    }
 
-    let resp_frame = cmd.execute(&store);
-    Ok(RedisResponse { frame: resp_frame })
+    if !cmd.allowed_unauthenticated() {
+        if let Err(denied) = backend.acl.check(session.user(), &cmd) {
+            return Ok(acl_denied(&backend, session, &cmd, denied));
+        }
+    }
+
+    // RESP3 connections can tell pushes from replies and are not restricted
+    if session.protocol() < 3 && session.is_subscribed() && !cmd.allowed_in_subscribed_context() {
+        return Ok(RedisResponse {
+            frame: SimpleError::new(
+                "ERR only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE are allowed in this context",
+            )
+            .into(),
+            close: false,
+        });
+    }
+
+    // CLIENT CACHING only applies to the command right after it
+    let keep_caching = matches!(cmd, Command::Client(ClientCommand::Caching(_)));
+    let shutdown = matches!(cmd, Command::Shutdown(_));
+    let resp_frame = cmd.execute(&backend, session);
+    if !keep_caching {
+        session.set_caching(None);
+    }
+    Ok(RedisResponse {
+        frame: resp_frame,
+        // like Redis, a successful SHUTDOWN doesn't reply
+        close: shutdown && backend.shutdown.is_requested(),
+    })
+}
+
+// Record the denial in the ACL LOG and tell the client. A client whose user
+// was deleted is disconnected.
+fn acl_denied(
+    backend: &Backend,
+    session: &Session,
+    cmd: &Command,
+    denied: Denied,
+) -> RedisResponse {
+    let (reason, object, message) = match denied {
+        Denied::NoUser => {
+            warn!(
+                "closing client {}: user '{}' no longer exists",
+                session.id(),
+                session.user()
+            );
+            return RedisResponse {
+                frame: RespNullBulkString.into(),
+                close: true,
+            };
+        }
+        Denied::Command => {
+            let name = match cmd.acl_name() {
+                (name, Some(sub)) => format!("{}|{}", name, sub),
+                (name, None) => name.to_string(),
+            };
+            let message = format!(
+                "NOPERM User {} has no permissions to run the '{}' command",
+                session.user(),
+                name
+            );
+            (DenyReason::Command, name, message)
+        }
+        Denied::Key(key) => (
+            DenyReason::Key,
+            String::from_utf8_lossy(&key).into_owned(),
+            "NOPERM No permissions to access a key".to_string(),
+        ),
+        Denied::Channel(channel) => (
+            DenyReason::Channel,
+            String::from_utf8_lossy(&channel).into_owned(),
+            "NOPERM No permissions to access a channel".to_string(),
+        ),
+    };
+    backend.acl.log_denied(
+        reason,
+        object,
+        session.user().to_string(),
+        client_info(session),
+        backend.config().acllog_max_len,
+    );
+    RedisResponse {
+        frame: SimpleError::new(message).into(),
+        close: false,
+    }
 }
 
 impl Encoder<RespFrame> for RespFrameCodec {
     type Error = anyhow::Error;
     fn encode(&mut self, frame: RespFrame, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        dst.extend_from_slice(&frame.encode());
+        let frame = if self.protocol < 3 {
+            frame.into_resp2()
+        } else {
+            frame
+        };
+        frame.encode_to(dst);
         Ok(())
     }
 }
@@ -71,10 +383,60 @@ impl Decoder for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match RespFrame::decode(src) {
-            Ok(frame) => Ok(Some(frame)),
-            Err(RespError::NotComplete) => Ok(None),
-            Err(e) => Err(e.into()),
+        loop {
+            let inline = !self.parser.is_partial()
+                && src.first().is_some_and(|b| !RespFrame::is_type_byte(*b));
+            if !inline {
+                return Ok(self.parser.parse(src)?);
+            }
+            match parse_inline(src, self.parser.limits().max_inline_len)? {
+                // empty lines are skipped
+                Some(args) if args.is_empty() => continue,
+                Some(args) => return Ok(Some(args.into())),
+                None => return Ok(None),
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::resp::{BulkString, RespArray};
+
+    use super::*;
+
+    fn command(args: &[&str]) -> RespFrame {
+        RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::from(*arg).into())
+                .collect::<Vec<_>>(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_carries_password() {
+        assert!(carries_password(&command(&["AUTH", "x"])));
+        assert!(carries_password(&command(&["acl", "SETUSER", "u", ">x"])));
+        assert!(carries_password(&command(&[
+            "CONFIG",
+            "SET",
+            "requirepass",
+            "x"
+        ])));
+        assert!(carries_password(&command(&[
+            "config",
+            "set",
+            "maxmemory",
+            "1gb"
+        ])));
+
+        assert!(!carries_password(&command(&[
+            "CONFIG",
+            "GET",
+            "requirepass"
+        ])));
+        assert!(!carries_password(&command(&["ACL", "WHOAMI"])));
+        assert!(!carries_password(&command(&["SET", "requirepass", "x"])));
+    }
+}
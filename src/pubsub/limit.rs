@@ -0,0 +1,107 @@
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::config::parse_memory;
+
+// `client-output-buffer-limit pubsub <hard> <soft> <soft-seconds>`
+//
+// A subscriber is disconnected as soon as its queued output reaches the hard
+// limit, or once it has stayed at or above the soft limit for longer than
+// `soft_seconds`. A limit of 0 disables it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientOutputBufferLimit {
+    pub hard: usize,
+    pub soft: usize,
+    pub soft_seconds: Duration,
+}
+
+impl Default for ClientOutputBufferLimit {
+    fn default() -> Self {
+        Self {
+            hard: 32 * 1024 * 1024,
+            soft: 8 * 1024 * 1024,
+            soft_seconds: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ClientOutputBufferLimit {
+    // `soft_since` remembers when the soft limit was first reached and is
+    // reset once usage drops below it again.
+    pub fn is_exceeded(&self, used: usize, soft_since: &mut Option<Instant>, now: Instant) -> bool {
+        if self.hard > 0 && used >= self.hard {
+            return true;
+        }
+
+        if self.soft > 0 && used >= self.soft {
+            let since = *soft_since.get_or_insert(now);
+            now.duration_since(since) > self.soft_seconds
+        } else {
+            *soft_since = None;
+            false
+        }
+    }
+}
+
+impl FromStr for ClientOutputBufferLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        match parts.as_slice() {
+            [class, hard, soft, seconds] => {
+                if !class.eq_ignore_ascii_case("pubsub") {
+                    return Err(format!("unsupported client class '{}'", class));
+                }
+                let seconds = seconds
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid soft seconds '{}'", seconds))?;
+                Ok(Self {
+                    hard: parse_memory(hard)?,
+                    soft: parse_memory(soft)?,
+                    soft_seconds: Duration::from_secs(seconds),
+                })
+            }
+            _ => Err("expected '<class> <hard limit> <soft limit> <soft seconds>'".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_limit() {
+        let limit: ClientOutputBufferLimit = "pubsub 32mb 8mb 60".parse().unwrap();
+        assert_eq!(limit, ClientOutputBufferLimit::default());
+
+        let limit: ClientOutputBufferLimit = "pubsub 0 1k 5".parse().unwrap();
+        assert_eq!(limit.hard, 0);
+        assert_eq!(limit.soft, 1000);
+        assert_eq!(limit.soft_seconds, Duration::from_secs(5));
+
+        assert!("normal 0 0 0".parse::<ClientOutputBufferLimit>().is_err());
+        assert!("pubsub 1xb 0 0".parse::<ClientOutputBufferLimit>().is_err());
+    }
+
+    #[test]
+    fn test_limit_exceeded() {
+        let limit: ClientOutputBufferLimit = "pubsub 100 10 2".parse().unwrap();
+        let now = Instant::now();
+        let mut since = None;
+
+        assert!(!limit.is_exceeded(5, &mut since, now));
+        assert!(limit.is_exceeded(100, &mut since, now));
+
+        assert!(!limit.is_exceeded(10, &mut since, now));
+        assert_eq!(since, Some(now));
+        assert!(!limit.is_exceeded(20, &mut since, now + Duration::from_secs(2)));
+        assert!(limit.is_exceeded(20, &mut since, now + Duration::from_secs(3)));
+
+        assert!(!limit.is_exceeded(9, &mut since, now + Duration::from_secs(4)));
+        assert_eq!(since, None);
+    }
+}
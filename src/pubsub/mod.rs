@@ -0,0 +1,317 @@
+mod limit;
+mod notify;
+mod registry;
+
+pub use limit::ClientOutputBufferLimit;
+pub use notify::NotifyFlags;
+pub use registry::ChannelRegistry;
+
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        RwLock,
+    },
+};
+
+use bytes::Bytes;
+
+use crate::{
+    resp::{BulkString, RespFrame, RespNullBulkString, RespPush},
+    session::{ClientHandle, Session},
+};
+
+// Pub/Sub hub shared by all connections.
+#[derive(Debug, Default)]
+pub struct PubSub {
+    pub(crate) channels: ChannelRegistry,
+    pub(crate) patterns: ChannelRegistry,
+    // Sharded channels (SSUBSCRIBE / SPUBLISH) hash to slots like keys and are
+    // kept in their own registry, they never see messages sent with PUBLISH.
+    pub(crate) shard: ChannelRegistry,
+    output_buffer_limit: RwLock<ClientOutputBufferLimit>,
+    notify_keyspace_events: AtomicU32,
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // SUBSCRIBE and PSUBSCRIBE return the number of channels and patterns the
+    // client is subscribed to afterwards.
+    pub fn subscribe(&self, session: &mut Session, channel: Bytes) -> usize {
+        subscribe(
+            &self.channels,
+            &mut session.channels,
+            channel,
+            &session.handle,
+        );
+        session.channels.len() + session.patterns.len()
+    }
+
+    pub fn unsubscribe(&self, session: &mut Session, channel: &[u8]) -> usize {
+        unsubscribe(
+            &self.channels,
+            &mut session.channels,
+            channel,
+            &session.handle,
+        );
+        session.channels.len() + session.patterns.len()
+    }
+
+    pub fn unsubscribe_all(&self, session: &mut Session) -> Vec<Bytes> {
+        unsubscribe_all(&self.channels, &mut session.channels, &session.handle)
+    }
+
+    pub fn psubscribe(&self, session: &mut Session, pattern: Bytes) -> usize {
+        subscribe(
+            &self.patterns,
+            &mut session.patterns,
+            pattern,
+            &session.handle,
+        );
+        session.channels.len() + session.patterns.len()
+    }
+
+    pub fn punsubscribe(&self, session: &mut Session, pattern: &[u8]) -> usize {
+        unsubscribe(
+            &self.patterns,
+            &mut session.patterns,
+            pattern,
+            &session.handle,
+        );
+        session.channels.len() + session.patterns.len()
+    }
+
+    pub fn punsubscribe_all(&self, session: &mut Session) -> Vec<Bytes> {
+        unsubscribe_all(&self.patterns, &mut session.patterns, &session.handle)
+    }
+
+    // Returns the number of shard channels the client is subscribed to afterwards.
+    pub fn ssubscribe(&self, session: &mut Session, channel: Bytes) -> usize {
+        subscribe(
+            &self.shard,
+            &mut session.shard_channels,
+            channel,
+            &session.handle,
+        );
+        session.shard_channels.len()
+    }
+
+    pub fn sunsubscribe(&self, session: &mut Session, channel: &[u8]) -> usize {
+        unsubscribe(
+            &self.shard,
+            &mut session.shard_channels,
+            channel,
+            &session.handle,
+        );
+        session.shard_channels.len()
+    }
+
+    pub fn sunsubscribe_all(&self, session: &mut Session) -> Vec<Bytes> {
+        unsubscribe_all(&self.shard, &mut session.shard_channels, &session.handle)
+    }
+
+    // Deliver to subscribers of the channel and of every matching pattern,
+    // returning the number of receivers.
+    pub fn publish(&self, channel: &[u8], message: BulkString) -> usize {
+        let limit = self.output_buffer_limit();
+        let frame = message_frame("message", channel, message.clone());
+        let receivers = self.channels.publish(channel, frame, &limit);
+        let pattern_frame = |pattern: &[u8]| pmessage_frame(pattern, channel, message.clone());
+        receivers
+            + self
+                .patterns
+                .publish_matching(channel, pattern_frame, &limit)
+    }
+
+    pub fn spublish(&self, channel: &[u8], message: BulkString) -> usize {
+        let frame = message_frame("smessage", channel, message);
+        self.shard
+            .publish(channel, frame, &self.output_buffer_limit())
+    }
+
+    pub fn output_buffer_limit(&self) -> ClientOutputBufferLimit {
+        *self.output_buffer_limit.read().unwrap()
+    }
+
+    pub fn set_output_buffer_limit(&self, limit: ClientOutputBufferLimit) {
+        *self.output_buffer_limit.write().unwrap() = limit;
+    }
+
+    pub fn notify_keyspace_events(&self) -> NotifyFlags {
+        NotifyFlags::from_bits(self.notify_keyspace_events.load(Ordering::Relaxed))
+    }
+
+    pub fn set_notify_keyspace_events(&self, flags: NotifyFlags) {
+        self.notify_keyspace_events
+            .store(flags.bits(), Ordering::Relaxed);
+    }
+
+    // Publish `__keyspace@<db>__:<key>` and/or `__keyevent@<db>__:<event>`
+    // when the event class is enabled by `notify-keyspace-events`.
+    pub fn notify_keyspace_event(&self, class: NotifyFlags, event: &str, key: &[u8], db: usize) {
+        let flags = self.notify_keyspace_events();
+        if !flags.intersects(class) {
+            return;
+        }
+
+        if flags.contains(NotifyFlags::KEYSPACE) {
+            let mut channel = format!("__keyspace@{}__:", db).into_bytes();
+            channel.extend_from_slice(key);
+            self.publish(&channel, BulkString::from(event));
+        }
+        if flags.contains(NotifyFlags::KEYEVENT) {
+            let channel = format!("__keyevent@{}__:{}", db, event);
+            self.publish(channel.as_bytes(), BulkString::from(key));
+        }
+    }
+}
+
+fn subscribe(
+    registry: &ChannelRegistry,
+    subscribed: &mut BTreeSet<Bytes>,
+    channel: Bytes,
+    client: &ClientHandle,
+) {
+    if subscribed.insert(channel.clone()) {
+        registry.subscribe(channel, client.clone());
+    }
+}
+
+fn unsubscribe(
+    registry: &ChannelRegistry,
+    subscribed: &mut BTreeSet<Bytes>,
+    channel: &[u8],
+    client: &ClientHandle,
+) {
+    if subscribed.remove(channel) {
+        registry.unsubscribe(channel, client.id());
+    }
+}
+
+// Unsubscribe from everything in `subscribed`, returning the channels in order.
+fn unsubscribe_all(
+    registry: &ChannelRegistry,
+    subscribed: &mut BTreeSet<Bytes>,
+    client: &ClientHandle,
+) -> Vec<Bytes> {
+    let channels = std::mem::take(subscribed);
+    for channel in &channels {
+        registry.unsubscribe(channel, client.id());
+    }
+    channels.into_iter().collect()
+}
+
+// Pub/sub frames are pushes, RESP2 connections receive them as arrays.
+
+// `[kind, channel, payload]`, e.g. `message` or `smessage`.
+pub fn message_frame(kind: &str, channel: &[u8], payload: BulkString) -> RespFrame {
+    RespPush::new(vec![
+        BulkString::from(kind).into(),
+        BulkString::from(channel).into(),
+        payload.into(),
+    ])
+    .into()
+}
+
+// `[pmessage, pattern, channel, payload]`
+pub fn pmessage_frame(pattern: &[u8], channel: &[u8], payload: BulkString) -> RespFrame {
+    RespPush::new(vec![
+        BulkString::from("pmessage").into(),
+        BulkString::from(pattern).into(),
+        BulkString::from(channel).into(),
+        payload.into(),
+    ])
+    .into()
+}
+
+// `[kind, channel, count]` confirming a (un)subscription. The channel is a
+// null bulk string when unsubscribing without being subscribed to anything.
+pub fn subscription_frame(kind: &str, channel: Option<&[u8]>, count: usize) -> RespFrame {
+    let channel = match channel {
+        Some(channel) => BulkString::from(channel).into(),
+        None => RespNullBulkString.into(),
+    };
+    RespPush::new(vec![
+        BulkString::from(kind).into(),
+        channel,
+        RespFrame::Integer(count as i64),
+    ])
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_patterns() {
+        let pubsub = PubSub::new();
+        let (mut s1, mut rx1) = Session::new(1);
+        let (mut s2, mut rx2) = Session::new(2);
+
+        assert_eq!(
+            pubsub.subscribe(&mut s1, Bytes::from_static(b"news.tech")),
+            1
+        );
+        assert_eq!(pubsub.psubscribe(&mut s1, Bytes::from_static(b"news.*")), 2);
+        assert_eq!(pubsub.psubscribe(&mut s2, Bytes::from_static(b"n*")), 1);
+        assert_eq!(
+            pubsub.ssubscribe(&mut s2, Bytes::from_static(b"news.tech")),
+            1
+        );
+
+        assert_eq!(pubsub.publish(b"news.tech", "rust".into()), 3);
+        assert_eq!(
+            rx1.try_recv().unwrap().frame,
+            message_frame("message", b"news.tech", "rust".into())
+        );
+        assert_eq!(
+            rx1.try_recv().unwrap().frame,
+            pmessage_frame(b"news.*", b"news.tech", "rust".into())
+        );
+        assert_eq!(
+            rx2.try_recv().unwrap().frame,
+            pmessage_frame(b"n*", b"news.tech", "rust".into())
+        );
+        assert!(rx2.try_recv().is_err());
+
+        assert_eq!(pubsub.punsubscribe_all(&mut s1), vec!["news.*"]);
+        assert_eq!(pubsub.unsubscribe(&mut s1, b"news.tech"), 0);
+        assert_eq!(pubsub.publish(b"news.tech", "go".into()), 1);
+    }
+
+    #[test]
+    fn test_notify_keyspace_event() {
+        let pubsub = PubSub::new();
+        let (mut session, mut rx) = Session::new(1);
+        pubsub.psubscribe(&mut session, Bytes::from_static(b"__key*__:*"));
+
+        pubsub.notify_keyspace_event(NotifyFlags::STRING, "set", b"foo", 0);
+        assert!(rx.try_recv().is_err());
+
+        pubsub.set_notify_keyspace_events("K$".parse().unwrap());
+        pubsub.notify_keyspace_event(NotifyFlags::GENERIC, "del", b"foo", 0);
+        assert!(rx.try_recv().is_err());
+
+        pubsub.notify_keyspace_event(NotifyFlags::STRING, "set", b"foo", 0);
+        assert_eq!(
+            rx.try_recv().unwrap().frame,
+            pmessage_frame(b"__key*__:*", b"__keyspace@0__:foo", "set".into())
+        );
+        assert!(rx.try_recv().is_err());
+
+        pubsub.set_notify_keyspace_events("KEA".parse().unwrap());
+        pubsub.notify_keyspace_event(NotifyFlags::STRING, "set", b"foo", 0);
+        assert_eq!(
+            rx.try_recv().unwrap().frame,
+            pmessage_frame(b"__key*__:*", b"__keyspace@0__:foo", "set".into())
+        );
+        assert_eq!(
+            rx.try_recv().unwrap().frame,
+            pmessage_frame(b"__key*__:*", b"__keyevent@0__:set", "foo".into())
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::{fmt, str::FromStr};
+
+// Event classes selected by `notify-keyspace-events`.
+// https://redis.io/docs/latest/develop/use/keyspace-notifications/#configuration
+//
+// Every class Redis knows is accepted, so that existing config strings load,
+// but the server has no key expiry or eviction: `x` and `e`, also enabled by
+// `A`, never fire. PubSub::notify_keyspace_event is where they would be
+// published from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NotifyFlags(u32);
+
+impl NotifyFlags {
+    pub const KEYSPACE: NotifyFlags = NotifyFlags(1 << 0); // K
+    pub const KEYEVENT: NotifyFlags = NotifyFlags(1 << 1); // E
+    pub const GENERIC: NotifyFlags = NotifyFlags(1 << 2); // g
+    pub const STRING: NotifyFlags = NotifyFlags(1 << 3); // $
+    pub const LIST: NotifyFlags = NotifyFlags(1 << 4); // l
+    pub const SET: NotifyFlags = NotifyFlags(1 << 5); // s
+    pub const HASH: NotifyFlags = NotifyFlags(1 << 6); // h
+    pub const ZSET: NotifyFlags = NotifyFlags(1 << 7); // z
+    pub const EXPIRED: NotifyFlags = NotifyFlags(1 << 8); // x
+    pub const EVICTED: NotifyFlags = NotifyFlags(1 << 9); // e
+    pub const STREAM: NotifyFlags = NotifyFlags(1 << 10); // t
+    pub const KEY_MISS: NotifyFlags = NotifyFlags(1 << 11); // m
+    pub const NEW: NotifyFlags = NotifyFlags(1 << 12); // n
+
+    // `A` is an alias for "g$lshzxet", key miss and new key events must be
+    // enabled explicitly.
+    pub const ALL: NotifyFlags = NotifyFlags(
+        Self::GENERIC.0
+            | Self::STRING.0
+            | Self::LIST.0
+            | Self::SET.0
+            | Self::HASH.0
+            | Self::ZSET.0
+            | Self::EXPIRED.0
+            | Self::EVICTED.0
+            | Self::STREAM.0,
+    );
+
+    const CHARS: [(char, NotifyFlags); 12] = [
+        ('g', Self::GENERIC),
+        ('$', Self::STRING),
+        ('l', Self::LIST),
+        ('s', Self::SET),
+        ('h', Self::HASH),
+        ('z', Self::ZSET),
+        ('x', Self::EXPIRED),
+        ('e', Self::EVICTED),
+        ('t', Self::STREAM),
+        ('K', Self::KEYSPACE),
+        ('E', Self::KEYEVENT),
+        ('m', Self::KEY_MISS),
+    ];
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub fn from_bits(bits: u32) -> Self {
+        NotifyFlags(bits)
+    }
+
+    pub fn contains(self, other: NotifyFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersects(self, other: NotifyFlags) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl std::ops::BitOr for NotifyFlags {
+    type Output = NotifyFlags;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        NotifyFlags(self.0 | rhs.0)
+    }
+}
+
+impl FromStr for NotifyFlags {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut flags = NotifyFlags::default();
+        for c in s.chars() {
+            flags = flags
+                | match c {
+                    'A' => Self::ALL,
+                    'n' => Self::NEW,
+                    _ => Self::CHARS
+                        .iter()
+                        .find(|(ch, _)| *ch == c)
+                        .map(|(_, flag)| *flag)
+                        .ok_or_else(|| format!("invalid event class '{}'", c))?,
+                };
+        }
+        Ok(flags)
+    }
+}
+
+// Canonical form used by CONFIG GET, e.g. "AKE".
+impl fmt::Display for NotifyFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = String::new();
+        if self.contains(Self::ALL) {
+            s.push('A');
+        }
+        for (c, flag) in Self::CHARS {
+            let in_all = Self::ALL.contains(flag);
+            if self.contains(flag) && !(in_all && s.starts_with('A')) {
+                s.push(c);
+            }
+        }
+        if self.contains(Self::NEW) {
+            s.push('n');
+        }
+        f.write_str(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify_flags() {
+        let flags: NotifyFlags = "KEA".parse().unwrap();
+        assert!(flags.contains(NotifyFlags::KEYSPACE | NotifyFlags::KEYEVENT));
+        assert!(flags.contains(NotifyFlags::STRING));
+        assert!(!flags.intersects(NotifyFlags::KEY_MISS | NotifyFlags::NEW));
+        assert_eq!(flags.to_string(), "AKE");
+
+        let flags: NotifyFlags = "Ex$mn".parse().unwrap();
+        assert_eq!(flags.to_string(), "$xEmn");
+        assert_eq!(NotifyFlags::default().to_string(), "");
+
+        assert!("Kq".parse::<NotifyFlags>().is_err());
+    }
+}
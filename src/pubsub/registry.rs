@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use dashmap::DashMap;
+
+use crate::{
+    glob::glob_match,
+    resp::{RespEncode, RespFrame},
+    session::ClientHandle,
+};
+
+use super::ClientOutputBufferLimit;
+
+// Maps channel names to the connections subscribed to them.
+#[derive(Debug, Default)]
+pub struct ChannelRegistry {
+    channels: DashMap<Bytes, HashMap<u64, ClientHandle>>,
+}
+
+impl ChannelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns true if the client was not subscribed to the channel before.
+    pub fn subscribe(&self, channel: Bytes, client: ClientHandle) -> bool {
+        self.channels
+            .entry(channel)
+            .or_default()
+            .insert(client.id(), client)
+            .is_none()
+    }
+
+    // Returns true if the client was subscribed to the channel.
+    pub fn unsubscribe(&self, channel: &[u8], client_id: u64) -> bool {
+        let removed = match self.channels.get_mut(channel) {
+            Some(mut clients) => clients.remove(&client_id).is_some(),
+            None => false,
+        };
+        self.channels
+            .remove_if(channel, |_, clients| clients.is_empty());
+        removed
+    }
+
+    // Queue the frame for every subscriber, returning how many received it.
+    // Subscribers whose output queue goes over `limit` are disconnected.
+    pub fn publish(
+        &self,
+        channel: &[u8],
+        frame: RespFrame,
+        limit: &ClientOutputBufferLimit,
+    ) -> usize {
+        let clients: Vec<ClientHandle> = match self.channels.get(channel) {
+            Some(clients) => clients.values().cloned().collect(),
+            None => return 0,
+        };
+        send_all(clients, frame, limit)
+    }
+
+    // Like `publish`, to every pattern in the registry matching `channel`.
+    // `frame` builds the message for a pattern.
+    pub fn publish_matching(
+        &self,
+        channel: &[u8],
+        frame: impl Fn(&[u8]) -> RespFrame,
+        limit: &ClientOutputBufferLimit,
+    ) -> usize {
+        // the clients are collected first, sending doesn't hold the map
+        let matching: Vec<(Bytes, Vec<ClientHandle>)> = self
+            .channels
+            .iter()
+            .filter(|entry| glob_match(entry.key(), channel))
+            .map(|entry| {
+                (
+                    entry.key().clone(),
+                    entry.value().values().cloned().collect(),
+                )
+            })
+            .collect();
+        matching
+            .into_iter()
+            .map(|(pattern, clients)| send_all(clients, frame(&pattern), limit))
+            .sum()
+    }
+
+    // Active channels, i.e. channels with at least one subscriber, optionally
+    // filtered by a glob-style pattern.
+    pub fn channels(&self, pattern: Option<&[u8]>) -> Vec<Bytes> {
+        let mut channels: Vec<Bytes> = self
+            .channels
+            .iter()
+            .filter(|entry| !entry.value().is_empty())
+            .map(|entry| entry.key().clone())
+            .filter(|channel| pattern.is_none_or(|p| glob_match(p, channel)))
+            .collect();
+        channels.sort();
+        channels
+    }
+
+    pub fn contains(&self, channel: &[u8], client_id: u64) -> bool {
+        self.channels
+            .get(channel)
+            .is_some_and(|clients| clients.contains_key(&client_id))
+    }
+
+    pub fn numsub(&self, channel: &[u8]) -> usize {
+        self.channels
+            .get(channel)
+            .map_or(0, |clients| clients.len())
+    }
+}
+
+fn send_all(
+    clients: Vec<ClientHandle>,
+    frame: RespFrame,
+    limit: &ClientOutputBufferLimit,
+) -> usize {
+    let size = frame.encode().len();
+    clients
+        .into_iter()
+        .filter(|client| client.send(frame.clone(), size, limit))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        resp::SimpleString,
+        session::{CloseReason, Session},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_subscribe_publish() {
+        let registry = ChannelRegistry::new();
+        let (s1, mut rx1) = Session::new(1);
+        let (s2, mut rx2) = Session::new(2);
+
+        assert!(registry.subscribe(Bytes::from_static(b"news"), s1.handle().clone()));
+        assert!(!registry.subscribe(Bytes::from_static(b"news"), s1.handle().clone()));
+        assert!(registry.subscribe(Bytes::from_static(b"news"), s2.handle().clone()));
+        assert!(registry.subscribe(Bytes::from_static(b"sport"), s2.handle().clone()));
+
+        let limit = ClientOutputBufferLimit::default();
+        let frame: RespFrame = SimpleString::new("hello").into();
+        assert_eq!(registry.publish(b"news", frame.clone(), &limit), 2);
+        assert_eq!(rx1.try_recv().unwrap().frame, frame);
+        assert_eq!(rx2.try_recv().unwrap().frame, frame);
+        assert_eq!(registry.publish(b"weather", frame, &limit), 0);
+
+        assert_eq!(registry.numsub(b"news"), 2);
+        assert_eq!(registry.channels(None), vec!["news", "sport"]);
+        assert_eq!(registry.channels(Some(&b"n*"[..])), vec!["news"]);
+
+        assert!(registry.unsubscribe(b"news", 1));
+        assert!(!registry.unsubscribe(b"news", 1));
+        assert!(registry.unsubscribe(b"sport", 2));
+        assert_eq!(registry.channels(None), vec!["news"]);
+    }
+
+    #[test]
+    fn test_publish_output_buffer_limit() {
+        let registry = ChannelRegistry::new();
+        let (slow, mut rx) = Session::new(1);
+        registry.subscribe(Bytes::from_static(b"news"), slow.handle().clone());
+
+        let limit: ClientOutputBufferLimit = "pubsub 16 0 0".parse().unwrap();
+        let frame: RespFrame = SimpleString::new("hello").into();
+        assert_eq!(registry.publish(b"news", frame.clone(), &limit), 1);
+        assert_eq!(slow.handle().pending_bytes(), 8);
+
+        // the subscriber did not read the first message, the second one hits the hard limit
+        assert_eq!(registry.publish(b"news", frame.clone(), &limit), 0);
+        assert_eq!(
+            slow.handle().close_reason(),
+            Some(CloseReason::OutputBufferLimit)
+        );
+        assert_eq!(registry.publish(b"news", frame, &limit), 0);
+
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -1,10 +1,10 @@
 use std::ops::Deref;
 
-use bytes::{Buf, BytesMut};
+use bytes::{BufMut, BytesMut};
 
 use super::{
-    calculate_total_length, extract_fixed_data, parse_length, RespDecode, RespEncode, RespError,
-    RespFrame, BUF_CAP, CRLF_LEN,
+    calculate_total_length, decode_aggregate, extract_fixed_data, parse_length, write_header,
+    RespDecode, RespEncode, RespError, RespFrame, RespLimits,
 };
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
@@ -17,48 +17,36 @@ pub struct RespNullArray;
 // https://redis.io/docs/latest/develop/reference/protocol-spec/#arrays
 // `*<number-of-elements>\r\n<element-1>...<element-n>`
 impl RespEncode for RespArray {
-    fn encode(self) -> Vec<u8> {
-        let mut buf: Vec<u8> = Vec::with_capacity(BUF_CAP);
-
-        buf.extend_from_slice(&format!("*{}\r\n", self.0.len()).into_bytes());
-
-        for frame in self.0 {
-            buf.extend_from_slice(&frame.encode())
+    fn encode_to(&self, buf: &mut BytesMut) {
+        write_header(buf, b'*', self.0.len());
+        for frame in &self.0 {
+            frame.encode_to(buf);
         }
-        buf
     }
 }
 
 impl RespDecode for RespArray {
     const PREFIX: &'static str = "*";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let total_len = calculate_total_length(buf, end, len, Self::PREFIX)?;
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
+        match decode_aggregate::<Self>(buf)? {
+            RespFrame::Array(frame) => Ok(frame),
+            frame => Err(RespError::InvalidFrameType(format!(
+                "expect: Array, actual: {:?}",
+                frame
+            ))),
         }
-
-        buf.advance(end + CRLF_LEN);
-
-        let mut frames = Vec::with_capacity(len);
-        for _ in 0..len {
-            let frame = RespFrame::decode(buf)?;
-            frames.push(frame);
-        }
-
-        Ok(RespArray::new(frames))
     }
 
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        calculate_total_length(buf, end, len, Self::PREFIX)
+        calculate_total_length(buf, end, len, Self::PREFIX, &RespLimits::default())
     }
 }
 
 // NullArray: `*-1\r\n`
 impl RespEncode for RespNullArray {
-    fn encode(self) -> Vec<u8> {
-        b"*-1\r\n".to_vec()
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.put_slice(b"*-1\r\n");
     }
 }
 
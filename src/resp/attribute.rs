@@ -0,0 +1,140 @@
+use bytes::BytesMut;
+
+use super::{
+    calculate_total_length, decode_aggregate, parse_length, write_header, RespDecode, RespEncode,
+    RespError, RespFrame, RespLimits, RespMap,
+};
+
+// https://redis.io/docs/latest/develop/reference/protocol-spec/#attributes
+// |<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n><frame>
+// Auxiliary data such as key popularity attached to the frame that follows
+// it, the frame itself is what answers the request.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct RespAttribute {
+    pub(crate) attributes: RespMap,
+    pub(crate) frame: Box<RespFrame>,
+}
+
+impl RespAttribute {
+    pub fn new(attributes: RespMap, frame: impl Into<RespFrame>) -> Self {
+        RespAttribute {
+            attributes,
+            frame: Box::new(frame.into()),
+        }
+    }
+
+    pub fn attributes(&self) -> &RespMap {
+        &self.attributes
+    }
+
+    pub fn frame(&self) -> &RespFrame {
+        &self.frame
+    }
+
+    pub fn into_parts(self) -> (RespMap, RespFrame) {
+        (self.attributes, *self.frame)
+    }
+}
+
+impl RespEncode for RespAttribute {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        write_header(buf, b'|', self.attributes.len());
+        for (key, value) in &self.attributes.0 {
+            key.encode_to(buf);
+            value.encode_to(buf);
+        }
+        self.frame.encode_to(buf);
+    }
+}
+
+impl RespDecode for RespAttribute {
+    const PREFIX: &'static str = "|";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        match decode_aggregate::<Self>(buf)? {
+            RespFrame::Attribute(frame) => Ok(frame),
+            frame => Err(RespError::InvalidFrameType(format!(
+                "expect: Attribute, actual: {:?}",
+                frame
+            ))),
+        }
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        calculate_total_length(buf, end, len, Self::PREFIX, &RespLimits::default())
+    }
+}
+
+impl RespFrame {
+    // Attach attributes to the frame, they are sent right before it.
+    pub fn with_attributes(self, attributes: RespMap) -> RespFrame {
+        RespAttribute::new(attributes, self).into()
+    }
+
+    // The frame with any attributes stripped.
+    pub fn into_inner(self) -> RespFrame {
+        match self {
+            RespFrame::Attribute(attr) => attr.frame.into_inner(),
+            frame => frame,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resp::{BulkString, RespArray, SimpleString};
+
+    use super::*;
+
+    fn popularity() -> RespMap {
+        let mut popularity = RespMap::new();
+        popularity.insert(SimpleString::new("a"), 0.1923);
+        popularity.insert(SimpleString::new("b"), 0.0012);
+        let mut attributes = RespMap::new();
+        attributes.insert(SimpleString::new("key-popularity"), popularity);
+        attributes
+    }
+
+    #[test]
+    fn test_attribute_encode() {
+        let reply: RespFrame = RespArray::new(vec![2039123.into(), 9543892.into()]).into();
+        let frame = reply.with_attributes(popularity());
+
+        assert_eq!(
+            frame.encode(),
+            b"|1\r\n+key-popularity\r\n%2\r\n+a\r\n,0.1923\r\n+b\r\n,0.0012\r\n*2\r\n:+2039123\r\n:+9543892\r\n"
+        );
+    }
+
+    #[test]
+    fn test_attribute_decode() -> anyhow::Result<()> {
+        let data = b"|1\r\n+key-popularity\r\n%2\r\n+a\r\n,0.1923\r\n+b\r\n,0.0012\r\n*2\r\n:2039123\r\n:9543892\r\n$2\r\nok\r\n";
+        let mut buf = BytesMut::from(&data[..]);
+
+        assert_eq!(RespFrame::expect_length(&buf)?, data.len() - 8);
+        let frame = RespFrame::decode(&mut buf)?;
+        let RespFrame::Attribute(attr) = frame.clone() else {
+            panic!("expected an attribute frame, got {:?}", frame);
+        };
+        assert_eq!(attr.attributes(), &popularity());
+        assert_eq!(
+            frame.into_inner(),
+            RespArray::new(vec![2039123.into(), 9543892.into()]).into()
+        );
+
+        // the next frame is untouched
+        assert_eq!(RespFrame::decode(&mut buf)?, BulkString::new("ok").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_attribute_not_complete() {
+        let mut buf = BytesMut::from(&b"|1\r\n+ttl\r\n:3600\r\n"[..]);
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        buf.extend_from_slice(b"+OK\r\n");
+        assert_eq!(
+            RespFrame::decode(&mut buf).map(RespFrame::into_inner),
+            Ok(SimpleString::new("OK").into())
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::ops::Deref;
+
+use bytes::{BufMut, BytesMut};
+
+use super::{extract_simple_frame_data, RespDecode, RespEncode, RespError, CRLF, CRLF_LEN};
+
+// https://redis.io/docs/latest/develop/reference/protocol-spec/#big-numbers
+// ([+|-]<number>\r\n
+// Integers outside of the i64 range, kept as their decimal representation.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct BigNumber(pub(crate) String);
+
+impl BigNumber {
+    pub fn new(s: impl Into<String>) -> Result<Self, RespError> {
+        let s = s.into();
+        let digits = s.strip_prefix(['+', '-']).unwrap_or(&s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RespError::InvalidFrame(format!(
+                "invalid big number: {}",
+                s
+            )));
+        }
+        Ok(BigNumber(s))
+    }
+}
+
+impl RespEncode for BigNumber {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.put_u8(b'(');
+        buf.put_slice(self.0.as_bytes());
+        buf.put_slice(CRLF);
+    }
+}
+
+impl RespDecode for BigNumber {
+    const PREFIX: &'static str = "(";
+    fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
+        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
+        let data = buf.split_to(end + CRLF_LEN);
+        let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
+        BigNumber::new(s)
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
+        Ok(end + CRLF_LEN)
+    }
+}
+
+impl From<i64> for BigNumber {
+    fn from(value: i64) -> Self {
+        BigNumber(value.to_string())
+    }
+}
+
+impl From<i128> for BigNumber {
+    fn from(value: i128) -> Self {
+        BigNumber(value.to_string())
+    }
+}
+
+impl Deref for BigNumber {
+    type Target = String;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::resp::RespFrame;
+
+    use super::*;
+
+    #[test]
+    fn test_big_number() -> anyhow::Result<()> {
+        let frame: RespFrame =
+            BigNumber::new("3492890328409238509324850943850943825024385")?.into();
+        assert_eq!(
+            frame.encode(),
+            b"(3492890328409238509324850943850943825024385\r\n"
+        );
+
+        let mut buf = BytesMut::from(&b"(-170141183460469231731687303715884105728\r\n"[..]);
+        let num = BigNumber::decode(&mut buf)?;
+        assert_eq!(num, BigNumber::from(i128::MIN));
+
+        let mut buf = BytesMut::from(&b"(12a\r\n"[..]);
+        assert!(BigNumber::decode(&mut buf).is_err());
+        Ok(())
+    }
+}
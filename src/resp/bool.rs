@@ -1,14 +1,12 @@
+use bytes::{BufMut, BytesMut};
+
 use super::{extract_fixed_data, RespDecode, RespEncode, RespError};
 
 // #<t|f>\r\n
 // https://redis.io/docs/latest/develop/reference/protocol-spec/#booleans
 impl RespEncode for bool {
-    fn encode(self) -> Vec<u8> {
-        if self {
-            b"#t\r\n".to_vec()
-        } else {
-            b"#f\r\n".to_vec()
-        }
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.put_slice(if *self { b"#t\r\n" } else { b"#f\r\n" });
     }
 }
 
@@ -0,0 +1,76 @@
+use std::ops::Deref;
+
+use bytes::{BufMut, BytesMut};
+
+use super::{
+    bulk_expect_length, extract_bulk_data, write_header, RespDecode, RespEncode, RespError, CRLF,
+};
+
+// https://redis.io/docs/latest/develop/reference/protocol-spec/#bulk-errors
+// !<length>\r\n<error>\r\n
+// Like SimpleError, but the message may contain CR or LF.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct BulkError(pub(crate) String);
+
+impl BulkError {
+    pub fn new(s: impl Into<String>) -> Self {
+        BulkError(s.into())
+    }
+}
+
+impl RespEncode for BulkError {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        write_header(buf, b'!', self.len());
+        buf.put_slice(self.as_bytes());
+        buf.put_slice(CRLF);
+    }
+}
+
+impl RespDecode for BulkError {
+    const PREFIX: &'static str = "!";
+    fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
+        let data = extract_bulk_data(buf, Self::PREFIX)?;
+        Ok(BulkError::new(String::from_utf8_lossy(&data)))
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        bulk_expect_length(buf, Self::PREFIX)
+    }
+}
+
+impl From<&str> for BulkError {
+    fn from(value: &str) -> Self {
+        BulkError(value.to_string())
+    }
+}
+
+impl Deref for BulkError {
+    type Target = String;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::resp::RespFrame;
+
+    use super::*;
+
+    #[test]
+    fn test_bulk_error() -> anyhow::Result<()> {
+        let frame: RespFrame = BulkError::new("SYNTAX invalid syntax").into();
+        assert_eq!(frame.encode(), b"!21\r\nSYNTAX invalid syntax\r\n");
+
+        let mut buf = BytesMut::from(&b"!8\r\nERR a\r\nb\r\n+OK\r\n"[..]);
+        let err = BulkError::decode(&mut buf)?;
+        assert_eq!(err, BulkError::new("ERR a\r\nb"));
+        assert_eq!(&buf[..], b"+OK\r\n");
+
+        let mut buf = BytesMut::from(&b"!8\r\nERR a"[..]);
+        assert_eq!(BulkError::decode(&mut buf), Err(RespError::NotComplete));
+        Ok(())
+    }
+}
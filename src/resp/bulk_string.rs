@@ -1,32 +1,40 @@
 use std::ops::Deref;
 
-use bytes::Buf;
+use bytes::{BufMut, Bytes, BytesMut};
 
-use super::{extract_fixed_data, parse_length, RespDecode, RespEncode, RespError, CRLF_LEN};
+use super::{
+    bulk_expect_length, extract_bulk_data, extract_fixed_data, write_header, RespDecode,
+    RespEncode, RespError, CRLF,
+};
 
-const BULK_STRING_PREFIX: &'static str = "$";
+const BULK_STRING_PREFIX: &str = "$";
 
 // https://redis.io/docs/latest/develop/reference/protocol-spec/#bulk-strings
 //$<length>\r\n<data>\r\n
+//
+// The payload is reference counted, decoding slices it off the read buffer and
+// cloning a frame (e.g. for every GET of a stored value) doesn't copy it.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
-pub struct BulkString(pub(crate) Vec<u8>);
+pub struct BulkString(pub(crate) Bytes);
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
 pub struct RespNullBulkString;
 
 impl BulkString {
-    pub fn new(s: impl Into<Vec<u8>>) -> Self {
+    pub fn new(s: impl Into<Bytes>) -> Self {
         BulkString(s.into())
     }
+
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
 }
 
 impl RespEncode for BulkString {
-    fn encode(self) -> Vec<u8> {
-        let mut buf: Vec<u8> = Vec::with_capacity(self.len() + 16);
-        buf.extend_from_slice(&format!("{}{}\r\n", BULK_STRING_PREFIX, self.len()).into_bytes());
-        buf.extend_from_slice(&self);
-        buf.extend_from_slice(b"\r\n");
-        buf
+    fn encode_to(&self, buf: &mut BytesMut) {
+        write_header(buf, b'$', self.len());
+        buf.put_slice(self);
+        buf.put_slice(CRLF);
     }
 }
 
@@ -35,30 +43,18 @@ impl RespEncode for BulkString {
 impl RespDecode for BulkString {
     const PREFIX: &'static str = BULK_STRING_PREFIX;
     fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?; // 2, 3
-        println!("{}, {}", end, len);
-        let remain_data = &buf[end + CRLF_LEN..]; // abc\r\n
-        println!("{:?}", String::from_utf8_lossy(remain_data));
-
-        if remain_data.len() < len + CRLF_LEN {
-            return Err(RespError::NotComplete);
-        }
-
-        buf.advance(end + CRLF_LEN);
-        let data = buf.split_to(len + CRLF_LEN); // abc\r\n
-        println!("{:?}", String::from_utf8_lossy(&data));
-
-        Ok(BulkString::new(data[..len].to_vec())) // data[..len] => abc
+        // the payload keeps pointing into the read buffer
+        let data = extract_bulk_data(buf, Self::PREFIX)?;
+        Ok(BulkString(data.freeze()))
     }
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        Ok(end + CRLF_LEN + len + CRLF_LEN)
+        bulk_expect_length(buf, Self::PREFIX)
     }
 }
 
 impl RespEncode for RespNullBulkString {
-    fn encode(self) -> Vec<u8> {
-        b"-1\r\n".to_vec()
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.put_slice(b"$-1\r\n");
     }
 }
 
@@ -80,7 +76,7 @@ impl AsRef<[u8]> for BulkString {
 }
 
 impl Deref for BulkString {
-    type Target = Vec<u8>;
+    type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -89,25 +85,37 @@ impl Deref for BulkString {
 
 impl From<&str> for BulkString {
     fn from(s: &str) -> Self {
-        BulkString(s.as_bytes().to_vec())
+        BulkString(Bytes::copy_from_slice(s.as_bytes()))
     }
 }
 
 impl From<String> for BulkString {
     fn from(s: String) -> Self {
-        BulkString(s.into_bytes())
+        BulkString(s.into_bytes().into())
     }
 }
 
 impl From<&[u8]> for BulkString {
     fn from(s: &[u8]) -> Self {
-        BulkString(s.to_vec())
+        BulkString(Bytes::copy_from_slice(s))
     }
 }
 
 impl<const N: usize> From<&[u8; N]> for BulkString {
     fn from(s: &[u8; N]) -> Self {
-        BulkString(s.to_vec())
+        BulkString(Bytes::copy_from_slice(s))
+    }
+}
+
+impl From<Vec<u8>> for BulkString {
+    fn from(s: Vec<u8>) -> Self {
+        BulkString(s.into())
+    }
+}
+
+impl From<Bytes> for BulkString {
+    fn from(s: Bytes) -> Self {
+        BulkString(s)
     }
 }
 
@@ -127,4 +135,17 @@ mod tests {
         assert_eq!(s, BulkString::new(b"abc".to_vec()));
         Ok(())
     }
+
+    #[test]
+    fn test_decode_zero_copy() -> anyhow::Result<()> {
+        let mut buf = BytesMut::from(&b"$5\r\nhello\r\n+OK\r\n"[..]);
+        let payload = buf[4..].as_ptr();
+
+        let s = BulkString::decode(&mut buf)?;
+        assert_eq!(&s[..], b"hello");
+        // the payload still lives in the read buffer's allocation
+        assert_eq!(s.as_ptr(), payload);
+        assert_eq!(&buf[..], b"+OK\r\n");
+        Ok(())
+    }
 }
@@ -0,0 +1,269 @@
+// Test vectors from the RESP3 specification, one or more per frame type:
+// https://redis.io/docs/latest/develop/reference/protocol-spec/
+//
+// Every vector must be measured exactly by `expect_length`, decode to the
+// expected frame both at once and fed a byte at a time to the parser, and
+// fail to decode with `NotComplete`, consuming nothing, when cut short.
+// Encoding the frame gives the canonical bytes, the same as the input unless
+// the spec allows another spelling.
+use bytes::BytesMut;
+
+use super::{
+    BigNumber, BulkError, BulkString, RespArray, RespAttribute, RespDecode, RespEncode, RespError,
+    RespFrame, RespMap, RespNull, RespNullArray, RespNullBulkString, RespParser, RespPush, RespSet,
+    SimpleError, SimpleString, VerbatimString,
+};
+
+struct Vector {
+    wire: &'static [u8],
+    frame: RespFrame,
+    // what the frame encodes to, when it differs from `wire`
+    canonical: Option<&'static [u8]>,
+}
+
+fn vector(wire: &'static [u8], frame: impl Into<RespFrame>) -> Vector {
+    Vector {
+        wire,
+        frame: frame.into(),
+        canonical: None,
+    }
+}
+
+fn alias(wire: &'static [u8], frame: impl Into<RespFrame>, canonical: &'static [u8]) -> Vector {
+    Vector {
+        wire,
+        frame: frame.into(),
+        canonical: Some(canonical),
+    }
+}
+
+fn bulk(s: &str) -> RespFrame {
+    BulkString::from(s).into()
+}
+
+fn simple(s: &str) -> RespFrame {
+    SimpleString::new(s).into()
+}
+
+fn vectors() -> Vec<Vector> {
+    let mut first_second = RespMap::new();
+    first_second.insert(simple("first"), RespFrame::Integer(1));
+    first_second.insert(simple("second"), RespFrame::Integer(2));
+
+    let mut popularity = RespMap::new();
+    popularity.insert(bulk("a"), RespFrame::Double(0.1923));
+    popularity.insert(bulk("b"), RespFrame::Double(0.0012));
+    let mut attributes = RespMap::new();
+    attributes.insert(simple("key-popularity"), popularity);
+
+    vec![
+        // simple strings
+        vector(b"+OK\r\n", simple("OK")),
+        vector(b"+\r\n", simple("")),
+        // simple errors
+        vector(
+            b"-ERR unknown command 'asdf'\r\n",
+            SimpleError::new("ERR unknown command 'asdf'"),
+        ),
+        vector(
+            b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n",
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value"),
+        ),
+        // integers, non-negative ones are written with an explicit sign
+        alias(b":0\r\n", RespFrame::Integer(0), b":+0\r\n"),
+        alias(b":1000\r\n", RespFrame::Integer(1000), b":+1000\r\n"),
+        vector(b":+1000\r\n", RespFrame::Integer(1000)),
+        vector(b":-1000\r\n", RespFrame::Integer(-1000)),
+        vector(
+            b":-9223372036854775808\r\n",
+            RespFrame::Integer(i64::MIN),
+        ),
+        // bulk strings
+        vector(b"$5\r\nhello\r\n", bulk("hello")),
+        vector(b"$0\r\n\r\n", bulk("")),
+        vector(b"$4\r\na\r\nb\r\n", bulk("a\r\nb")),
+        vector(b"$2\r\n\x00\xff\r\n", BulkString::from(&b"\x00\xff"[..])),
+        vector(b"$-1\r\n", RespNullBulkString),
+        // arrays
+        vector(b"*0\r\n", RespArray::new(vec![])),
+        vector(
+            b"*2\r\n$5\r\nhello\r\n$5\r\nworld\r\n",
+            RespArray::new(vec![bulk("hello"), bulk("world")]),
+        ),
+        alias(
+            b"*3\r\n:1\r\n:2\r\n:3\r\n",
+            RespArray::new(vec![1.into(), 2.into(), 3.into()]),
+            b"*3\r\n:+1\r\n:+2\r\n:+3\r\n",
+        ),
+        alias(
+            b"*5\r\n:1\r\n:2\r\n:3\r\n:4\r\n$5\r\nhello\r\n",
+            RespArray::new(vec![1.into(), 2.into(), 3.into(), 4.into(), bulk("hello")]),
+            b"*5\r\n:+1\r\n:+2\r\n:+3\r\n:+4\r\n$5\r\nhello\r\n",
+        ),
+        alias(
+            b"*2\r\n*3\r\n:1\r\n:2\r\n:3\r\n*2\r\n+Hello\r\n-World\r\n",
+            RespArray::new(vec![
+                RespArray::new(vec![1.into(), 2.into(), 3.into()]).into(),
+                RespArray::new(vec![simple("Hello"), SimpleError::new("World").into()]).into(),
+            ]),
+            b"*2\r\n*3\r\n:+1\r\n:+2\r\n:+3\r\n*2\r\n+Hello\r\n-World\r\n",
+        ),
+        vector(
+            b"*3\r\n$5\r\nhello\r\n$-1\r\n$5\r\nworld\r\n",
+            RespArray::new(vec![bulk("hello"), RespNullBulkString.into(), bulk("world")]),
+        ),
+        vector(b"*-1\r\n", RespNullArray),
+        // nulls
+        vector(b"_\r\n", RespNull),
+        // booleans
+        vector(b"#t\r\n", true),
+        vector(b"#f\r\n", false),
+        // doubles
+        vector(b",1.23\r\n", RespFrame::Double(1.23)),
+        alias(b",+1.23\r\n", RespFrame::Double(1.23), b",1.23\r\n"),
+        vector(b",-1.23\r\n", RespFrame::Double(-1.23)),
+        vector(b",10\r\n", RespFrame::Double(10.0)),
+        alias(b",1.5e3\r\n", RespFrame::Double(1500.0), b",1500\r\n"),
+        alias(b",-1E-7\r\n", RespFrame::Double(-1e-7), b",-1e-07\r\n"),
+        vector(b",1e+100\r\n", RespFrame::Double(1e100)),
+        vector(b",inf\r\n", RespFrame::Double(f64::INFINITY)),
+        vector(b",-inf\r\n", RespFrame::Double(f64::NEG_INFINITY)),
+        // big numbers
+        vector(
+            b"(3492890328409238509324850943850943825024385\r\n",
+            BigNumber::new("3492890328409238509324850943850943825024385").unwrap(),
+        ),
+        vector(b"(-1\r\n", BigNumber::new("-1").unwrap()),
+        // bulk errors
+        vector(
+            b"!21\r\nSYNTAX invalid syntax\r\n",
+            BulkError::new("SYNTAX invalid syntax"),
+        ),
+        // verbatim strings
+        vector(
+            b"=15\r\ntxt:Some string\r\n",
+            VerbatimString::txt("Some string"),
+        ),
+        vector(b"=4\r\nmkd:\r\n", VerbatimString::new(*b"mkd", "")),
+        // maps
+        alias(
+            b"%2\r\n+first\r\n:1\r\n+second\r\n:2\r\n",
+            first_second,
+            b"%2\r\n+first\r\n:+1\r\n+second\r\n:+2\r\n",
+        ),
+        vector(b"%0\r\n", RespMap::new()),
+        // attributes
+        alias(
+            b"|1\r\n+key-popularity\r\n%2\r\n$1\r\na\r\n,0.1923\r\n$1\r\nb\r\n,0.0012\r\n*2\r\n:2039123\r\n:9543892\r\n",
+            RespAttribute::new(
+                attributes,
+                RespArray::new(vec![2039123.into(), 9543892.into()]),
+            ),
+            b"|1\r\n+key-popularity\r\n%2\r\n$1\r\na\r\n,0.1923\r\n$1\r\nb\r\n,0.0012\r\n*2\r\n:+2039123\r\n:+9543892\r\n",
+        ),
+        // sets
+        alias(
+            b"~5\r\n+orange\r\n+apple\r\n#t\r\n:100\r\n:999\r\n",
+            RespSet::new(vec![
+                simple("orange"),
+                simple("apple"),
+                true.into(),
+                100.into(),
+                999.into(),
+            ]),
+            b"~5\r\n+orange\r\n+apple\r\n#t\r\n:+100\r\n:+999\r\n",
+        ),
+        // pushes
+        vector(
+            b">3\r\n$7\r\nmessage\r\n$7\r\nchannel\r\n$5\r\nhello\r\n",
+            RespPush::new(vec![bulk("message"), bulk("channel"), bulk("hello")]),
+        ),
+    ]
+}
+
+#[test]
+fn test_conformance_vectors() -> anyhow::Result<()> {
+    for v in vectors() {
+        let name = String::from_utf8_lossy(v.wire);
+
+        assert_eq!(RespFrame::expect_length(v.wire)?, v.wire.len(), "{}", name);
+
+        let mut buf = BytesMut::from(v.wire);
+        assert_eq!(RespFrame::decode(&mut buf)?, v.frame, "{}", name);
+        assert!(buf.is_empty(), "{}", name);
+
+        assert_eq!(v.frame.encode(), v.canonical.unwrap_or(v.wire), "{}", name);
+
+        for len in 0..v.wire.len() {
+            let prefix = &v.wire[..len];
+            // the length may already be known from the header
+            let length = RespFrame::expect_length(prefix);
+            assert!(
+                length == Err(RespError::NotComplete) || length == Ok(v.wire.len()),
+                "{} cut at {}: {:?}",
+                name,
+                len,
+                length
+            );
+            let mut buf = BytesMut::from(prefix);
+            assert_eq!(
+                RespFrame::decode(&mut buf),
+                Err(RespError::NotComplete),
+                "{} cut at {}",
+                name,
+                len
+            );
+            assert_eq!(&buf[..], prefix, "{} cut at {}", name, len);
+        }
+
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::new();
+        let mut frames = vec![];
+        for b in v.wire {
+            buf.extend_from_slice(&[*b]);
+            frames.extend(parser.parse(&mut buf)?);
+        }
+        assert_eq!(frames, vec![v.frame], "{}", name);
+        assert!(buf.is_empty() && !parser.is_partial(), "{}", name);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_conformance_nan() -> anyhow::Result<()> {
+    let mut buf = BytesMut::from(&b",nan\r\n"[..]);
+    let frame = RespFrame::decode(&mut buf)?;
+    assert!(matches!(frame, RespFrame::Double(d) if d.is_nan()));
+    assert_eq!(frame.encode(), b",nan\r\n");
+    Ok(())
+}
+
+#[test]
+fn test_conformance_invalid() {
+    for wire in [
+        &b"?foo\r\n"[..],
+        b":abc\r\n",
+        b":1.5\r\n",
+        b"$3\r\nabcd\r\n",
+        b"$abc\r\nabc\r\n",
+        b"*-2\r\n",
+        b"_x\r\n",
+        b"#x\r\n",
+        b",.5\r\n",
+        b",1.\r\n",
+        b",Infinity\r\n",
+        b"(12a\r\n",
+        b"!3\r\nabcd\r\n",
+        b"=3\r\ntxt\r\n",
+        b"%1\r\n+a\r\n?b\r\n",
+    ] {
+        let name = String::from_utf8_lossy(wire);
+        let mut buf = BytesMut::from(wire);
+        let err = RespFrame::decode(&mut buf).expect_err(&name);
+        assert_ne!(err, RespError::NotComplete, "{}", name);
+
+        let mut buf = BytesMut::from(wire);
+        let err = RespParser::new().parse(&mut buf).expect_err(&name);
+        assert_ne!(err, RespError::NotComplete, "{}", name);
+    }
+}
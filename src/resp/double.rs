@@ -1,21 +1,17 @@
-use super::{extract_simple_frame_data, RespDecode, RespEncode, RespError, BUF_CAP, CRLF_LEN};
+use bytes::{Buf, BufMut, BytesMut};
 
-const DOUBLE_PREFIX: &'static str = ",";
+use super::{extract_simple_frame_data, RespDecode, RespEncode, RespError, CRLF, CRLF_LEN};
+
+const DOUBLE_PREFIX: &str = ",";
 
 // https://redis.io/docs/latest/develop/reference/protocol-spec/#doubles
 // ,[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n
+// ,inf\r\n ,-inf\r\n ,nan\r\n
 impl RespEncode for f64 {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(BUF_CAP);
-        let ret = if self.abs() > 1e+8 || self.abs() < 1e-8 {
-            format!("{}{:+e}\r\n", DOUBLE_PREFIX, self)
-        } else {
-            let sign = if self < 0.0 { "" } else { "+" };
-            format!("{}{}{}\r\n", DOUBLE_PREFIX, sign, self)
-        };
-
-        buf.extend_from_slice(&ret.into_bytes());
-        buf
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.put_slice(DOUBLE_PREFIX.as_bytes());
+        buf.put_slice(format_double(*self).as_bytes());
+        buf.put_slice(CRLF);
     }
 }
 
@@ -23,9 +19,9 @@ impl RespDecode for f64 {
     const PREFIX: &'static str = DOUBLE_PREFIX;
     fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
         let end = extract_simple_frame_data(buf, Self::PREFIX)?;
-        let data = buf.split_to(end + CRLF_LEN);
-        let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
-        Ok(s.parse()?)
+        let d = parse_double(&buf[Self::PREFIX.len()..end])?;
+        buf.advance(end + CRLF_LEN);
+        Ok(d)
     }
 
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
@@ -34,6 +30,96 @@ impl RespDecode for f64 {
     }
 }
 
+// Format a double the way Redis replies with it, both in RESP3 doubles and
+// RESP2 bulk strings: `inf`, `-inf` and `nan` for the special values,
+// otherwise the shortest digits that parse back to the same value (at most
+// 17 significant ones) laid out like printf's `%.17g`.
+pub fn format_double(d: f64) -> String {
+    if d.is_nan() {
+        return "nan".to_string();
+    }
+    if d.is_infinite() {
+        return if d > 0.0 { "inf" } else { "-inf" }.to_string();
+    }
+
+    // `{:e}` gives the shortest round-trip digits, e.g. `-1.25e-7`
+    let sci = format!("{:e}", d);
+    let (mantissa, exp) = sci.split_once('e').expect("{:e} has an exponent");
+    let exp: i32 = exp.parse().expect("{:e} exponent is an integer");
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+
+    if !(-4..17).contains(&exp) {
+        let (first, rest) = digits.split_at(1);
+        let dot = if rest.is_empty() { "" } else { "." };
+        let exp_sign = if exp < 0 { '-' } else { '+' };
+        format!("{sign}{first}{dot}{rest}e{exp_sign}{:02}", exp.abs())
+    } else if exp < 0 {
+        let zeros = "0".repeat((-exp - 1) as usize);
+        format!("{sign}0.{zeros}{digits}")
+    } else {
+        let int_len = exp as usize + 1;
+        if digits.len() > int_len {
+            format!("{sign}{}.{}", &digits[..int_len], &digits[int_len..])
+        } else {
+            format!("{sign}{digits:0<int_len$}")
+        }
+    }
+}
+
+// Parse a double as the spec spells it. Rust's float parsing on its own is
+// more lenient, it accepts `.5`, `1.`, `infinity` or `NaN`.
+pub fn parse_double(s: &[u8]) -> Result<f64, RespError> {
+    match s {
+        b"inf" => return Ok(f64::INFINITY),
+        b"-inf" => return Ok(f64::NEG_INFINITY),
+        b"nan" => return Ok(f64::NAN),
+        _ => {}
+    }
+    if !is_double_syntax(s) {
+        return Err(RespError::InvalidFrame(format!(
+            "invalid double: {}",
+            String::from_utf8_lossy(s)
+        )));
+    }
+    // only ASCII is left at this point
+    Ok(std::str::from_utf8(s).expect("ASCII").parse()?)
+}
+
+// [<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]
+fn is_double_syntax(s: &[u8]) -> bool {
+    fn sign(s: &[u8]) -> &[u8] {
+        s.strip_prefix(b"+")
+            .or_else(|| s.strip_prefix(b"-"))
+            .unwrap_or(s)
+    }
+    // at least one digit
+    fn digits(s: &[u8]) -> Option<&[u8]> {
+        let n = s.iter().take_while(|b| b.is_ascii_digit()).count();
+        (n > 0).then(|| &s[n..])
+    }
+
+    let Some(mut s) = digits(sign(s)) else {
+        return false;
+    };
+    if let Some(rest) = s.strip_prefix(b".") {
+        let Some(rest) = digits(rest) else {
+            return false;
+        };
+        s = rest;
+    }
+    if let Some(rest) = s.strip_prefix(b"e").or_else(|| s.strip_prefix(b"E")) {
+        let Some(rest) = digits(sign(rest)) else {
+            return false;
+        };
+        s = rest;
+    }
+    s.is_empty()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::resp::RespFrame;
@@ -45,10 +131,43 @@ mod tests {
     #[test]
     fn test_encode_double() {
         let frame: RespFrame = 1000.24.into();
-        assert_eq!(frame.encode(), b",+1000.24\r\n");
+        assert_eq!(frame.encode(), b",1000.24\r\n");
 
         let frame1: RespFrame = (-8.9999).into();
         assert_eq!(frame1.encode(), b",-8.9999\r\n");
+
+        assert_eq!(f64::INFINITY.encode(), b",inf\r\n");
+        assert_eq!(f64::NEG_INFINITY.encode(), b",-inf\r\n");
+        assert_eq!(f64::NAN.encode(), b",nan\r\n");
+    }
+
+    #[test]
+    fn test_format_double() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "-0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (0.1, "0.1"),
+            (0.1 + 0.2, "0.30000000000000004"),
+            (1.0 / 3.0, "0.3333333333333333"),
+            (3.0e-5, "3e-05"),
+            (0.0001, "0.0001"),
+            (0.00001, "1e-05"),
+            (1e16, "10000000000000000"),
+            (1e17, "1e+17"),
+            (123456789.125, "123456789.125"),
+            (1.5e300, "1.5e+300"),
+            (-2.5e-300, "-2.5e-300"),
+            (f64::MAX, "1.7976931348623157e+308"),
+            (f64::MIN_POSITIVE, "2.2250738585072014e-308"),
+            (5e-324, "5e-324"),
+            (9007199254740993.0, "9007199254740992"),
+        ];
+        for (d, expected) in cases {
+            assert_eq!(format_double(d), expected, "{:?}", d);
+            assert_eq!(parse_double(expected.as_bytes()).unwrap(), d);
+        }
     }
 
     #[test]
@@ -66,6 +185,38 @@ mod tests {
 
         let num1 = f64::decode(&mut buf)?;
         assert_eq!(num1, -9.88);
+
+        let mut buf = BytesMut::from(&b",1.5E+3\r\n,10\r\n,-inf\r\n,nan\r\n"[..]);
+        assert_eq!(f64::decode(&mut buf)?, 1500.0);
+        assert_eq!(f64::decode(&mut buf)?, 10.0);
+        assert_eq!(f64::decode(&mut buf)?, f64::NEG_INFINITY);
+        assert!(f64::decode(&mut buf)?.is_nan());
+        assert!(buf.is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_decode_double_invalid() {
+        for data in [
+            &b",.5\r\n"[..],
+            b",1.\r\n",
+            b",1e\r\n",
+            b",+\r\n",
+            b",1.5.5\r\n",
+            b",infinity\r\n",
+            b",+inf\r\n",
+            b",NaN\r\n",
+            b",1_000\r\n",
+            b", 1\r\n",
+        ] {
+            let mut buf = BytesMut::from(data);
+            assert!(
+                matches!(f64::decode(&mut buf), Err(RespError::InvalidFrame(_))),
+                "{}",
+                String::from_utf8_lossy(data)
+            );
+            // nothing is consumed on errors
+            assert_eq!(&buf[..], data);
+        }
+    }
 }
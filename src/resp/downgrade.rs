@@ -0,0 +1,89 @@
+use super::{
+    double::format_double, BulkString, RespArray, RespFrame, RespNullBulkString, RespPush,
+    SimpleError,
+};
+
+impl RespFrame {
+    // Convert RESP3-only types to their RESP2 counterparts for connections
+    // which did not negotiate RESP3 with HELLO:
+    // maps become flat arrays of keys and values, sets and pushes become
+    // arrays, doubles, big numbers and verbatim strings become bulk strings,
+    // booleans become integers, nulls become null bulk strings, bulk errors
+    // become simple errors and attributes are dropped.
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Map(map) => RespArray::new(
+                map.0
+                    .into_iter()
+                    .flat_map(|(k, v)| [k.into_resp2(), v.into_resp2()])
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Set(set) => into_resp2_array(set.0),
+            RespFrame::Push(RespPush(frames)) => into_resp2_array(frames),
+            RespFrame::Array(RespArray(frames)) => into_resp2_array(frames),
+            RespFrame::Double(d) => BulkString::from(format_double(d)).into(),
+            RespFrame::Boolean(b) => RespFrame::Integer(b as i64),
+            RespFrame::Null(_) => RespNullBulkString.into(),
+            RespFrame::BigNumber(n) => BulkString::from(n.0).into(),
+            RespFrame::VerbatimString(s) => BulkString::new(s.data).into(),
+            RespFrame::BulkError(e) => SimpleError::new(e.0.replace(['\r', '\n'], " ")).into(),
+            RespFrame::Attribute(attr) => attr.into_parts().1.into_resp2(),
+            frame => frame,
+        }
+    }
+}
+
+fn into_resp2_array(frames: Vec<RespFrame>) -> RespFrame {
+    RespArray::new(
+        frames
+            .into_iter()
+            .map(RespFrame::into_resp2)
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resp::{
+        BigNumber, BulkError, RespEncode, RespMap, RespNull, RespSet, SimpleString, VerbatimString,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_into_resp2() -> anyhow::Result<()> {
+        let mut map = RespMap::new();
+        map.insert(BulkString::from("proto"), 2);
+        map.insert(
+            SimpleString::new("flags"),
+            RespSet::new(vec![true.into(), RespNull.into()]),
+        );
+        let frame: RespFrame = map.into();
+        assert_eq!(
+            frame.into_resp2().encode(),
+            b"*4\r\n$5\r\nproto\r\n:+2\r\n+flags\r\n*2\r\n:+1\r\n$-1\r\n"
+        );
+
+        let frame: RespFrame = RespPush::new(vec![
+            BulkString::from("message").into(),
+            1.5.into(),
+            BigNumber::new("123456789012345678901234567890")?.into(),
+            VerbatimString::txt("text").into(),
+        ])
+        .into();
+        assert_eq!(
+            frame.into_resp2().encode(),
+            b"*4\r\n$7\r\nmessage\r\n$3\r\n1.5\r\n$30\r\n123456789012345678901234567890\r\n$4\r\ntext\r\n"
+        );
+
+        let frame: RespFrame = BulkError::new("ERR a\r\nb").into();
+        assert_eq!(frame.into_resp2().encode(), b"-ERR a  b\r\n");
+
+        let frame: RespFrame = SimpleString::new("OK").into();
+        let frame = frame.with_attributes(RespMap::new());
+        assert_eq!(frame.into_resp2().encode(), b"+OK\r\n");
+        Ok(())
+    }
+}
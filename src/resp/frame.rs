@@ -1,8 +1,10 @@
 use enum_dispatch::enum_dispatch;
 
 use super::{
-    null::RespNull, BulkString, RespArray, RespDecode, RespError, RespMap, RespNullArray,
-    RespNullBulkString, RespSet, SimpleError, SimpleString,
+    calculate_total_length, null::RespNull, parse_length, preview, scalar_length, BigNumber,
+    BulkError, BulkString, RespArray, RespAttribute, RespDecode, RespError, RespLimits, RespMap,
+    RespNullArray, RespNullBulkString, RespPush, RespSet, SimpleError, SimpleString,
+    VerbatimString,
 };
 
 #[enum_dispatch(RespEncode)]
@@ -20,6 +22,55 @@ pub enum RespFrame {
     Array(RespArray),
     NullArray(RespNullArray),
     Null(RespNull),
+    Push(RespPush),
+    BigNumber(BigNumber),
+    VerbatimString(VerbatimString),
+    BulkError(BulkError),
+    Attribute(RespAttribute),
+}
+
+impl RespFrame {
+    // Push frames carry out-of-band data and are not the reply to a request.
+    pub fn is_push(&self) -> bool {
+        matches!(self, RespFrame::Push(_))
+    }
+
+    // Whether `b` starts a RESP2 or RESP3 frame.
+    pub fn is_type_byte(b: u8) -> bool {
+        matches!(
+            b,
+            b'+' | b'-'
+                | b':'
+                | b'$'
+                | b'*'
+                | b'_'
+                | b'#'
+                | b','
+                | b'('
+                | b'!'
+                | b'='
+                | b'%'
+                | b'~'
+                | b'|'
+                | b'>'
+        )
+    }
+
+    // Like `expect_length`, against the given limits instead of the default
+    // ones, e.g. with `proto-max-bulk-len` from the config.
+    pub fn expect_length_with_limits(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+        let prefix = match buf.first() {
+            Some(b'*') if buf.starts_with(b"*-1\r\n") => return RespNullArray::expect_length(buf),
+            Some(b'*') => RespArray::PREFIX,
+            Some(b'~') => RespSet::PREFIX,
+            Some(b'>') => RespPush::PREFIX,
+            Some(b'%') => RespMap::PREFIX,
+            Some(b'|') => RespAttribute::PREFIX,
+            _ => return scalar_length(buf, limits),
+        };
+        let (end, len) = parse_length(buf, prefix)?;
+        calculate_total_length(buf, end, len, prefix, limits)
+    }
 }
 
 impl RespDecode for RespFrame {
@@ -60,26 +111,46 @@ impl RespDecode for RespFrame {
                 let frame = RespSet::decode(buf)?;
                 Ok(frame.into())
             }
-            Some(b'*') => match RespNullArray::decode(buf) {
-                Ok(f) => Ok(f.into()),
-                Err(RespError::NotComplete) => Err(RespError::NotComplete),
-                Err(_) => {
-                    let f = RespArray::decode(buf)?;
-                    Ok(f.into())
-                }
-            },
-            Some(b'$') => match RespNullBulkString::decode(buf) {
-                Ok(f) => Ok(f.into()),
-                Err(RespError::NotComplete) => Err(RespError::NotComplete),
-                Err(_) => {
-                    let f = BulkString::decode(buf)?;
-                    Ok(f.into())
-                }
-            },
+            Some(b'>') => {
+                let frame = RespPush::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'(') => {
+                let frame = BigNumber::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'=') => {
+                let frame = VerbatimString::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'!') => {
+                let frame = BulkError::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'|') => {
+                let frame = RespAttribute::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'*') if b"*-1\r\n".starts_with(&buf[..buf.len().min(5)]) => {
+                let frame = RespNullArray::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'*') => {
+                let frame = RespArray::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'$') if b"$-1\r\n".starts_with(&buf[..buf.len().min(5)]) => {
+                let frame = RespNullBulkString::decode(buf)?;
+                Ok(frame.into())
+            }
+            Some(b'$') => {
+                let frame = BulkString::decode(buf)?;
+                Ok(frame.into())
+            }
             None => Err(RespError::NotComplete),
             _ => Err(RespError::InvalidFrameType(format!(
-                "unknown frame type: {:?}",
-                buf
+                "unknown frame type: {}",
+                preview(buf)
             ))),
         }
     }
@@ -95,9 +166,20 @@ impl RespDecode for RespFrame {
             Some(b',') => f64::expect_length(buf),
             Some(b'%') => RespMap::expect_length(buf),
             Some(b'~') => RespSet::expect_length(buf),
+            Some(b'*') if buf.starts_with(b"*-1\r\n") => RespNullArray::expect_length(buf),
             Some(b'*') => RespArray::expect_length(buf),
+            Some(b'>') => RespPush::expect_length(buf),
+            Some(b'(') => BigNumber::expect_length(buf),
+            Some(b'=') => VerbatimString::expect_length(buf),
+            Some(b'!') => BulkError::expect_length(buf),
+            Some(b'|') => RespAttribute::expect_length(buf),
+            Some(b'$') if buf.starts_with(b"$-1\r\n") => RespNullBulkString::expect_length(buf),
             Some(b'$') => BulkString::expect_length(buf),
-            _ => Err(RespError::NotComplete),
+            None => Err(RespError::NotComplete),
+            _ => Err(RespError::InvalidFrameType(format!(
+                "unknown frame type: {}",
+                preview(buf)
+            ))),
         }
     }
 }
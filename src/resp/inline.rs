@@ -0,0 +1,173 @@
+use bytes::{Buf, BytesMut};
+
+use super::{BulkString, RespArray, RespError};
+
+// Inline commands are plain lines like `SET foo "hello world"\r\n`, as sent
+// by telnet or nc. Lines end at `\n` with an optional `\r` before it.
+pub fn parse_inline(buf: &mut BytesMut, max_len: usize) -> Result<Option<RespArray>, RespError> {
+    let Some(end) = buf.iter().position(|b| *b == b'\n') else {
+        if buf.len() > max_len {
+            return Err(RespError::LineTooLong);
+        }
+        return Ok(None);
+    };
+
+    let line = buf.split_to(end);
+    buf.advance(1);
+    let line = line.strip_suffix(b"\r").unwrap_or(&line);
+
+    let args = split_args(line).ok_or(RespError::UnbalancedQuotes)?;
+    let frames = args.into_iter().map(|arg| BulkString::from(arg).into());
+    Ok(Some(RespArray::new(frames.collect::<Vec<_>>())))
+}
+
+// Split a line into arguments following Redis' `sdssplitargs`: arguments are
+// separated by whitespace, `"..."` supports `\n`, `\r`, `\t`, `\b`, `\a` and
+// `\xHH` escapes, `'...'` only `\'`. A closing quote must be followed by
+// whitespace or the end of the line. Returns `None` for unbalanced quotes.
+pub fn split_args(line: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut args = Vec::new();
+    let mut i = 0;
+    loop {
+        while line.get(i).is_some_and(|b| is_space(*b)) {
+            i += 1;
+        }
+        if i == line.len() {
+            return Some(args);
+        }
+
+        let mut arg = Vec::new();
+        let mut quote = None;
+        loop {
+            match (quote, line.get(i).copied()) {
+                // unterminated quotes
+                (Some(_), None) => return None,
+                (None, None) => break,
+                (Some(b'"'), Some(b'\\')) => match (line.get(i + 1), hex_escape(&line[i..])) {
+                    (_, Some(byte)) => {
+                        arg.push(byte);
+                        i += 3;
+                    }
+                    (Some(c), None) => {
+                        arg.push(match c {
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'b' => 0x08,
+                            b'a' => 0x07,
+                            c => *c,
+                        });
+                        i += 1;
+                    }
+                    (None, None) => arg.push(b'\\'),
+                },
+                (Some(b'\''), Some(b'\\')) if line.get(i + 1) == Some(&b'\'') => {
+                    arg.push(b'\'');
+                    i += 1;
+                }
+                (Some(q), Some(c)) if c == q => {
+                    // the closing quote must end the argument
+                    if line.get(i + 1).is_some_and(|b| !is_space(*b)) {
+                        return None;
+                    }
+                    i += 1;
+                    break;
+                }
+                (Some(_), Some(c)) => arg.push(c),
+                (None, Some(b' ' | b'\n' | b'\r' | b'\t' | b'\0')) => break,
+                (None, Some(c @ (b'"' | b'\''))) => quote = Some(c),
+                (None, Some(c)) => arg.push(c),
+            }
+            i += 1;
+        }
+        args.push(arg);
+    }
+}
+
+// C's isspace
+fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c)
+}
+
+// `\xHH` at the start of `s`
+fn hex_escape(s: &[u8]) -> Option<u8> {
+    match s {
+        [b'\\', b'x', hi, lo, ..] => {
+            let hi = (*hi as char).to_digit(16)?;
+            let lo = (*lo as char).to_digit(16)?;
+            Some((hi * 16 + lo) as u8)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(line: &str) -> Option<Vec<String>> {
+        split_args(line.as_bytes()).map(|args| {
+            args.into_iter()
+                .map(|arg| String::from_utf8_lossy(&arg).into_owned())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            split("  set foo  bar "),
+            Some(vec!["set".into(), "foo".into(), "bar".into()])
+        );
+        assert_eq!(split(""), Some(vec![]));
+        assert_eq!(
+            split(r#"set "hello \"world\"\n" '\'it\'s\''"#),
+            Some(vec![
+                "set".into(),
+                "hello \"world\"\n".into(),
+                "'it's'".into()
+            ])
+        );
+        assert_eq!(split(r#""\x41\x4a\x4g""#), Some(vec!["AJx4g".into()]));
+        assert_eq!(split(r#"'a\nb'"#), Some(vec![r"a\nb".into()]));
+        // quotes can start in the middle of an argument
+        assert_eq!(split(r#"foo"bar baz""#), Some(vec!["foobar baz".into()]));
+        assert_eq!(split(r#""""#), Some(vec!["".into()]));
+
+        assert_eq!(split(r#""foo"bar"#), None);
+        assert_eq!(split(r#"'foo'bar"#), None);
+        assert_eq!(split(r#""unterminated"#), None);
+        assert_eq!(split(r#"'unterminated"#), None);
+        assert_eq!(split(r#""\"#), None);
+    }
+
+    #[test]
+    fn test_parse_inline() -> anyhow::Result<()> {
+        let mut buf = BytesMut::from(&b"SET foo \"a b\"\r\nPING\nGET"[..]);
+
+        let frame = parse_inline(&mut buf, 1024)?;
+        assert_eq!(
+            frame,
+            Some(RespArray::new(vec![
+                BulkString::from("SET").into(),
+                BulkString::from("foo").into(),
+                BulkString::from("a b").into(),
+            ]))
+        );
+        let frame = parse_inline(&mut buf, 1024)?;
+        assert_eq!(
+            frame,
+            Some(RespArray::new(vec![BulkString::from("PING").into()]))
+        );
+        assert_eq!(parse_inline(&mut buf, 1024)?, None);
+        assert_eq!(&buf[..], b"GET");
+
+        assert_eq!(parse_inline(&mut buf, 2), Err(RespError::LineTooLong));
+        let mut buf = BytesMut::from(&b"SET 'foo\r\n"[..]);
+        assert_eq!(
+            parse_inline(&mut buf, 1024),
+            Err(RespError::UnbalancedQuotes)
+        );
+        Ok(())
+    }
+}
@@ -1,10 +1,16 @@
-use super::{extract_simple_frame_data, RespDecode, RespEncode, RespError, CRLF_LEN};
+use bytes::{BufMut, BytesMut};
+
+use super::{extract_simple_frame_data, RespDecode, RespEncode, RespError, CRLF, CRLF_LEN};
 
 // :[<+|->]<value>\r\n
 impl RespEncode for i64 {
-    fn encode(self) -> Vec<u8> {
-        let sign = if self < 0 { "" } else { "+" };
-        format!(":{}{}\r\n", sign, self).into_bytes()
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.put_u8(b':');
+        if *self >= 0 {
+            buf.put_u8(b'+');
+        }
+        buf.put_slice(itoa::Buffer::new().format(*self).as_bytes());
+        buf.put_slice(CRLF);
     }
 }
 
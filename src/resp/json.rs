@@ -0,0 +1,288 @@
+use serde_json::{json, Map, Value};
+
+use super::{
+    double::format_double, BigNumber, BulkError, BulkString, RespArray, RespAttribute, RespError,
+    RespFrame, RespMap, RespNull, RespNullArray, RespNullBulkString, RespPush, RespSet,
+    SimpleError, SimpleString, VerbatimString,
+};
+
+// Lossless mapping between frames and JSON. Every frame but RESP3 null is an
+// object with a single key naming its type:
+//
+//     {"simple": "OK"}              {"error": "ERR oops"}
+//     {"integer": 1}                {"double": 1.5} or {"double": "-inf"}
+//     {"boolean": true}             {"big_number": "1234"}
+//     {"bulk": "foo"}               {"bulk": null} for the null bulk string
+//     {"bulk_error": "SYNTAX bad"}  {"verbatim": {"format": "txt", "data": "..."}}
+//     {"array": [...]}              {"array": null} for the null array
+//     {"set": [...]}                {"push": [...]}
+//     {"map": [[key, value], ...]}  {"attribute": {"attributes": [[key, value], ...], "frame": ...}}
+//     null
+//
+// Map keys can be any frame, so maps are lists of pairs. Binary data that is
+// not valid UTF-8 is written as `{"hex": "00ff"}` in place of the string.
+impl RespFrame {
+    pub fn to_json(&self) -> Value {
+        match self {
+            RespFrame::SimpleString(s) => json!({ "simple": &s[..] }),
+            RespFrame::Error(e) => json!({ "error": &e[..] }),
+            RespFrame::Integer(n) => json!({ "integer": n }),
+            RespFrame::Double(d) => json!({ "double": double_to_json(*d) }),
+            RespFrame::Boolean(b) => json!({ "boolean": b }),
+            RespFrame::Map(map) => json!({ "map": entries_to_json(map) }),
+            RespFrame::Set(set) => json!({ "set": frames_to_json(set) }),
+            RespFrame::BulkString(s) => json!({ "bulk": bytes_to_json(s) }),
+            RespFrame::NullBulkString(_) => json!({ "bulk": null }),
+            RespFrame::Array(array) => json!({ "array": frames_to_json(array) }),
+            RespFrame::NullArray(_) => json!({ "array": null }),
+            RespFrame::Null(_) => Value::Null,
+            RespFrame::Push(push) => json!({ "push": frames_to_json(push) }),
+            RespFrame::BigNumber(n) => json!({ "big_number": &n[..] }),
+            RespFrame::VerbatimString(s) => json!({
+                "verbatim": {
+                    "format": bytes_to_json(s.encoding()),
+                    "data": bytes_to_json(s.data()),
+                }
+            }),
+            RespFrame::BulkError(e) => json!({ "bulk_error": &e[..] }),
+            RespFrame::Attribute(attr) => json!({
+                "attribute": {
+                    "attributes": entries_to_json(attr.attributes()),
+                    "frame": attr.frame().to_json(),
+                }
+            }),
+        }
+    }
+
+    pub fn from_json(value: &Value) -> Result<RespFrame, RespError> {
+        let (kind, value) = match value {
+            Value::Null => return Ok(RespNull.into()),
+            Value::Object(object) if object.len() == 1 => object.iter().next().expect("one key"),
+            value => return Err(invalid(value)),
+        };
+
+        let frame = match (kind.as_str(), value) {
+            ("simple", Value::String(s)) => SimpleString::new(s.as_str()).into(),
+            ("error", Value::String(s)) => SimpleError::new(s.as_str()).into(),
+            ("integer", value) => value.as_i64().ok_or_else(|| invalid(value))?.into(),
+            ("double", value) => double_from_json(value)?.into(),
+            ("boolean", Value::Bool(b)) => (*b).into(),
+            ("map", value) => RespMap(entries_from_json(value)?).into(),
+            ("set", value) => RespSet::new(frames_from_json(value)?).into(),
+            ("bulk", Value::Null) => RespNullBulkString.into(),
+            ("bulk", value) => BulkString::from(bytes_from_json(value)?).into(),
+            ("array", Value::Null) => RespNullArray.into(),
+            ("array", value) => RespArray::new(frames_from_json(value)?).into(),
+            ("push", value) => RespPush::new(frames_from_json(value)?).into(),
+            ("big_number", Value::String(s)) => BigNumber::new(s.as_str())?.into(),
+            ("verbatim", Value::Object(object)) => {
+                let format = bytes_from_json(field(object, "format")?)?;
+                let format = format.try_into().map_err(|_| invalid(value))?;
+                let data = bytes_from_json(field(object, "data")?)?;
+                VerbatimString::new(format, data).into()
+            }
+            ("bulk_error", Value::String(s)) => BulkError::new(s.as_str()).into(),
+            ("attribute", Value::Object(object)) => {
+                let attributes = RespMap(entries_from_json(field(object, "attributes")?)?);
+                let frame = RespFrame::from_json(field(object, "frame")?)?;
+                RespAttribute::new(attributes, frame).into()
+            }
+            (_, value) => return Err(invalid(value)),
+        };
+        Ok(frame)
+    }
+}
+
+fn invalid(value: &Value) -> RespError {
+    RespError::InvalidFrame(format!("invalid JSON frame: {}", value))
+}
+
+fn field<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a Value, RespError> {
+    object
+        .get(name)
+        .ok_or_else(|| RespError::InvalidFrame(format!("JSON frame is missing `{}`", name)))
+}
+
+// JSON numbers can't be infinite or NaN.
+fn double_to_json(d: f64) -> Value {
+    if d.is_finite() {
+        json!(d)
+    } else {
+        json!(format_double(d))
+    }
+}
+
+fn double_from_json(value: &Value) -> Result<f64, RespError> {
+    match value {
+        Value::Number(n) => n.as_f64().ok_or_else(|| invalid(value)),
+        Value::String(s) => match s.as_str() {
+            "inf" => Ok(f64::INFINITY),
+            "-inf" => Ok(f64::NEG_INFINITY),
+            "nan" => Ok(f64::NAN),
+            _ => Err(invalid(value)),
+        },
+        value => Err(invalid(value)),
+    }
+}
+
+fn bytes_to_json(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => json!(s),
+        Err(_) => {
+            let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            json!({ "hex": hex })
+        }
+    }
+}
+
+fn bytes_from_json(value: &Value) -> Result<Vec<u8>, RespError> {
+    match value {
+        Value::String(s) => Ok(s.as_bytes().to_vec()),
+        Value::Object(object) if object.len() == 1 => {
+            let hex = field(object, "hex")?
+                .as_str()
+                .ok_or_else(|| invalid(value))?;
+            if hex.len() % 2 != 0 {
+                return Err(invalid(value));
+            }
+            (0..hex.len())
+                .step_by(2)
+                .map(|i| {
+                    hex.get(i..i + 2)
+                        .and_then(|b| u8::from_str_radix(b, 16).ok())
+                        .ok_or_else(|| invalid(value))
+                })
+                .collect()
+        }
+        value => Err(invalid(value)),
+    }
+}
+
+fn frames_to_json(frames: &[RespFrame]) -> Value {
+    frames.iter().map(RespFrame::to_json).collect()
+}
+
+fn frames_from_json(value: &Value) -> Result<Vec<RespFrame>, RespError> {
+    value
+        .as_array()
+        .ok_or_else(|| invalid(value))?
+        .iter()
+        .map(RespFrame::from_json)
+        .collect()
+}
+
+fn entries_to_json(map: &RespMap) -> Value {
+    map.iter()
+        .map(|(key, value)| json!([key.to_json(), value.to_json()]))
+        .collect()
+}
+
+// Pairs are kept as they are, duplicate keys included, so decoding is exact.
+fn entries_from_json(value: &Value) -> Result<Vec<(RespFrame, RespFrame)>, RespError> {
+    value
+        .as_array()
+        .ok_or_else(|| invalid(value))?
+        .iter()
+        .map(|entry| match entry.as_array().map(Vec::as_slice) {
+            Some([key, value]) => Ok((RespFrame::from_json(key)?, RespFrame::from_json(value)?)),
+            _ => Err(invalid(entry)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::resp::{RespDecode, RespEncode};
+
+    use super::*;
+
+    fn frames() -> Vec<RespFrame> {
+        let mut map = RespMap::new();
+        map.insert(BulkString::from("proto"), RespFrame::Integer(3));
+        map.insert(RespFrame::Integer(1), RespNull);
+        let mut attributes = RespMap::new();
+        attributes.insert(SimpleString::new("ttl"), RespFrame::Double(0.1));
+
+        vec![
+            SimpleString::new("OK").into(),
+            SimpleError::new("ERR oops").into(),
+            RespFrame::Integer(i64::MIN),
+            RespFrame::Double(0.1 + 0.2),
+            RespFrame::Double(f64::INFINITY),
+            RespFrame::Double(f64::NEG_INFINITY),
+            RespFrame::Double(-0.0),
+            false.into(),
+            map.into(),
+            RespSet::new(vec![BulkString::from("a").into()]).into(),
+            BulkString::from("héllo").into(),
+            BulkString::from(&b"\x00\xff\r\n"[..]).into(),
+            RespNullBulkString.into(),
+            RespArray::new(vec![RespArray::new(vec![]).into(), RespNull.into()]).into(),
+            RespNullArray.into(),
+            RespNull.into(),
+            RespPush::new(vec![BulkString::from("message").into()]).into(),
+            BigNumber::new("-3492890328409238509324850943850943825024385")
+                .unwrap()
+                .into(),
+            VerbatimString::new(*b"mkd", &b"# \xfe"[..]).into(),
+            BulkError::new("SYNTAX bad\r\nthing").into(),
+            RespAttribute::new(attributes, BulkString::from("v")).into(),
+        ]
+    }
+
+    #[test]
+    fn test_json_round_trip() -> anyhow::Result<()> {
+        for frame in frames() {
+            let json = frame.to_json();
+            assert_eq!(RespFrame::from_json(&json)?, frame, "{}", json);
+
+            // through text as well
+            let text = serde_json::to_string(&json)?;
+            let parsed = RespFrame::from_json(&serde_json::from_str(&text)?)?;
+            assert_eq!(parsed.encode(), frame.encode(), "{}", text);
+        }
+
+        let nan = RespFrame::from_json(&RespFrame::Double(f64::NAN).to_json())?;
+        assert!(matches!(nan, RespFrame::Double(d) if d.is_nan()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_json() -> anyhow::Result<()> {
+        let mut buf =
+            BytesMut::from(&b"*3\r\n$3\r\nGET\r\n$-1\r\n%1\r\n+a\r\n$2\r\n\xc0\x01\r\n"[..]);
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(
+            frame.to_json(),
+            json!({
+                "array": [
+                    { "bulk": "GET" },
+                    { "bulk": null },
+                    { "map": [[{ "simple": "a" }, { "bulk": { "hex": "c001" } }]] },
+                ]
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_json_invalid() {
+        for value in [
+            json!(1),
+            json!({}),
+            json!({ "bulk": "a", "integer": 1 }),
+            json!({ "unknown": 1 }),
+            json!({ "integer": 1.5 }),
+            json!({ "double": "infinity" }),
+            json!({ "bulk": { "hex": "abc" } }),
+            json!({ "bulk": { "hex": "zz" } }),
+            json!({ "map": [[{ "integer": 1 }]] }),
+            json!({ "verbatim": { "format": "text", "data": "" } }),
+            json!({ "attribute": { "attributes": [] } }),
+        ] {
+            assert!(RespFrame::from_json(&value).is_err(), "{}", value);
+        }
+    }
+}
@@ -1,62 +1,94 @@
-use std::{
-    collections::BTreeMap,
-    ops::{Deref, DerefMut},
-};
+use std::ops::Deref;
 
-use bytes::Buf;
+use bytes::BytesMut;
 
 use super::{
-    calculate_total_length, parse_length, RespDecode, RespEncode, RespError, RespFrame,
-    SimpleString, BUF_CAP, CRLF_LEN,
+    calculate_total_length, decode_aggregate, parse_length, write_header, RespDecode, RespEncode,
+    RespError, RespFrame, RespLimits,
 };
 
+// Keys may be any frame type, real servers mostly send bulk strings. Entries
+// keep the order they were inserted or received in.
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct RespMap(pub(crate) BTreeMap<String, RespFrame>);
+pub struct RespMap(pub(crate) Vec<(RespFrame, RespFrame)>);
 
 // %<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>
 impl RespEncode for RespMap {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(BUF_CAP);
-        buf.extend_from_slice(&format!("%{}\r\n", self.len()).into_bytes());
-
-        for (key, value) in self.0 {
-            buf.extend_from_slice(&SimpleString::new(key).encode());
-            buf.extend_from_slice(&value.encode());
+    fn encode_to(&self, buf: &mut BytesMut) {
+        write_header(buf, b'%', self.len());
+        for (key, value) in &self.0 {
+            key.encode_to(buf);
+            value.encode_to(buf);
         }
-        buf
     }
 }
 
 impl RespDecode for RespMap {
     const PREFIX: &'static str = "%";
-    fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let total_len = calculate_total_length(buf, end, len, Self::PREFIX)?;
-
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        match decode_aggregate::<Self>(buf)? {
+            RespFrame::Map(frame) => Ok(frame),
+            frame => Err(RespError::InvalidFrameType(format!(
+                "expect: Map, actual: {:?}",
+                frame
+            ))),
         }
-        buf.advance(end + CRLF_LEN);
-
-        let mut frames = RespMap::new();
-        for _ in 0..len {
-            let key = SimpleString::decode(buf)?;
-            let value = RespFrame::decode(buf)?;
-            frames.insert(key.0, value);
-        }
-        Ok(frames)
     }
 
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let total_len = calculate_total_length(buf, end, len, Self::PREFIX)?;
+        let total_len =
+            calculate_total_length(buf, end, len, Self::PREFIX, &RespLimits::default())?;
         Ok(total_len)
     }
 }
 
 impl RespMap {
     pub fn new() -> Self {
-        RespMap(BTreeMap::new())
+        RespMap(Vec::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        RespMap(Vec::with_capacity(capacity))
+    }
+
+    // Replaces the value of an existing equal key in place, returning the old
+    // value, otherwise appends the entry.
+    pub fn insert(
+        &mut self,
+        key: impl Into<RespFrame>,
+        value: impl Into<RespFrame>,
+    ) -> Option<RespFrame> {
+        let (key, value) = (key.into(), value.into());
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(std::mem::replace(v, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+
+    // Appends the entry without looking for an equal key, for keys known to
+    // be unique.
+    pub fn push(&mut self, key: impl Into<RespFrame>, value: impl Into<RespFrame>) {
+        self.0.push((key.into(), value.into()));
+    }
+
+    pub fn get(&self, key: &RespFrame) -> Option<&RespFrame> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    // Look up a key given as a simple or bulk string.
+    pub fn get_str(&self, key: &str) -> Option<&RespFrame> {
+        self.0
+            .iter()
+            .find(|(k, _)| match k {
+                RespFrame::SimpleString(s) => s.as_str() == key,
+                RespFrame::BulkString(s) => &s[..] == key.as_bytes(),
+                _ => false,
+            })
+            .map(|(_, v)| v)
     }
 }
 
@@ -67,14 +99,93 @@ impl Default for RespMap {
 }
 
 impl Deref for RespMap {
-    type Target = BTreeMap<String, RespFrame>;
+    type Target = Vec<(RespFrame, RespFrame)>;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl DerefMut for RespMap {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+// The keys are expected to be unique, like those of another map, they are
+// appended as they come.
+impl<K: Into<RespFrame>, V: Into<RespFrame>> FromIterator<(K, V)> for RespMap {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        RespMap(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::resp::{BulkString, RespArray, SimpleString};
+
+    use super::*;
+
+    #[test]
+    fn test_map_encode() {
+        let mut map = RespMap::new();
+        map.insert(SimpleString::new("first"), 1);
+        map.insert(BulkString::new("second"), 2);
+        map.insert(3, true);
+        assert_eq!(map.insert(SimpleString::new("first"), 10), Some(1.into()));
+
+        let frame: RespFrame = map.into();
+        assert_eq!(
+            frame.encode(),
+            b"%3\r\n+first\r\n:+10\r\n$6\r\nsecond\r\n:+2\r\n:+3\r\n#t\r\n"
+        );
+
+        // collecting and pushing append without looking up the keys
+        let mut map: RespMap = [
+            (SimpleString::new("first"), 1),
+            (SimpleString::new("second"), 2),
+        ]
+        .into_iter()
+        .collect();
+        map.push(3, true);
+        assert_eq!(
+            RespFrame::from(map).encode(),
+            b"%3\r\n+first\r\n:+1\r\n+second\r\n:+2\r\n:+3\r\n#t\r\n"
+        );
+    }
+
+    #[test]
+    fn test_map_decode_real_server() -> anyhow::Result<()> {
+        // HELLO 3 reply from a Redis 7.2 server
+        let data = b"%7\r\n$6\r\nserver\r\n$5\r\nredis\r\n$7\r\nversion\r\n$5\r\n7.2.4\r\n$5\r\nproto\r\n:3\r\n$2\r\nid\r\n:5\r\n$4\r\nmode\r\n$10\r\nstandalone\r\n$4\r\nrole\r\n$6\r\nmaster\r\n$7\r\nmodules\r\n*0\r\n";
+        let mut buf = BytesMut::from(&data[..]);
+
+        assert_eq!(RespMap::expect_length(&buf)?, data.len());
+        let map = RespMap::decode(&mut buf)?;
+        assert!(buf.is_empty());
+
+        let keys: Vec<&RespFrame> = map.iter().map(|(k, _)| k).collect();
+        assert_eq!(
+            keys,
+            ["server", "version", "proto", "id", "mode", "role", "modules"]
+                .iter()
+                .map(|k| BulkString::from(*k).into())
+                .collect::<Vec<RespFrame>>()
+                .iter()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(map.get_str("proto"), Some(&RespFrame::Integer(3)));
+        assert_eq!(
+            map.get(&BulkString::from("modules").into()),
+            Some(&RespArray::new(vec![]).into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_integer_keys() -> anyhow::Result<()> {
+        let mut buf = BytesMut::from(&b"%2\r\n:1\r\n+a\r\n:2\r\n+b\r\n"[..]);
+        let map = RespMap::decode(&mut buf)?;
+        assert_eq!(map.get(&2.into()), Some(&SimpleString::new("b").into()));
+        Ok(())
     }
 }
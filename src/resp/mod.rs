@@ -1,39 +1,67 @@
 pub mod array;
+pub mod attribute;
+pub mod big_number;
 pub mod bool;
+pub mod bulk_error;
 pub mod bulk_string;
+#[cfg(test)]
+mod conformance;
 pub mod double;
+pub mod downgrade;
 pub mod frame;
+pub mod inline;
 pub mod integer;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod map;
 pub mod null;
+pub mod output;
+pub mod parser;
+pub mod pretty;
+pub mod push;
+#[cfg(feature = "serde")]
+pub mod serde;
 pub mod set;
 pub mod simple_error;
 pub mod simple_string;
+pub mod verbatim_string;
 
-use bytes::{Buf, BytesMut};
+use bytes::{Buf, BufMut, BytesMut};
 use enum_dispatch::enum_dispatch;
 use thiserror::Error;
 
-#[allow(dead_code)]
-const BUF_CAP: usize = 4096;
-#[allow(dead_code)]
 const CRLF: &[u8] = b"\r\n";
-#[allow(dead_code)]
 const CRLF_LEN: usize = CRLF.len();
 
 pub use array::*;
-pub use bool::*;
+pub use attribute::*;
+pub use big_number::*;
+pub use bulk_error::*;
 pub use bulk_string::*;
 pub use frame::*;
+pub use inline::*;
 pub use map::*;
 pub use null::*;
+pub use output::*;
+pub use parser::*;
+pub use pretty::*;
+pub use push::*;
 pub use set::*;
 pub use simple_error::*;
 pub use simple_string::*;
+pub use verbatim_string::*;
 
+// Frames write themselves straight into the output buffer, nested frames
+// included, `encode` is only a convenience on top of that.
 #[enum_dispatch]
 pub trait RespEncode {
-    fn encode(self) -> Vec<u8>;
+    fn encode_to(&self, buf: &mut BytesMut);
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        self.encode_to(&mut buf);
+        buf.into()
+    }
 }
 
 pub trait RespDecode: Sized {
@@ -52,6 +80,16 @@ pub enum RespError {
     InvalidFrameLength(isize),
     #[error("Frame is not complete")]
     NotComplete,
+    #[error("Protocol error: invalid bulk length")]
+    BulkTooLong,
+    #[error("Protocol error: invalid multibulk length")]
+    TooManyElements,
+    #[error("Protocol error: nesting deeper than {0} levels")]
+    TooDeep(usize),
+    #[error("Protocol error: too big inline request")]
+    LineTooLong,
+    #[error("Protocol error: unbalanced quotes in request")]
+    UnbalancedQuotes,
 
     #[error("Parse error: {0}")]
     ParseIntError(#[from] std::num::ParseIntError),
@@ -66,7 +104,9 @@ pub fn extract_fixed_data(
     expect: &str,
     expect_type: &str,
 ) -> Result<(), RespError> {
-    if buf.len() < expect.len() {
+    // only a prefix of `expect` may still turn into it, e.g. `*0\r\n` can't
+    // become `*-1\r\n`
+    if buf.len() < expect.len() && expect.as_bytes().starts_with(buf) {
         return Err(RespError::NotComplete);
     }
 
@@ -74,7 +114,7 @@ pub fn extract_fixed_data(
         return Err(RespError::InvalidFrame(format!(
             "expect: {}, actual: {}",
             expect_type,
-            String::from_utf8_lossy(buf)
+            preview(buf)
         )));
     }
 
@@ -91,7 +131,7 @@ fn extract_simple_frame_data(buf: &[u8], prefix: &str) -> Result<usize, RespErro
         return Err(RespError::InvalidFrameType(format!(
             "expect: SimpleString({}), actual: {}",
             prefix,
-            String::from_utf8_lossy(buf)
+            preview(buf)
         )));
     }
 
@@ -99,17 +139,30 @@ fn extract_simple_frame_data(buf: &[u8], prefix: &str) -> Result<usize, RespErro
     Ok(end)
 }
 
-fn find_crlf(buf: &[u8], nth: usize) -> Option<usize> {
-    let mut count = 0;
-    for i in 1..buf.len() - 1 {
-        if buf[i] == b'\r' && buf[i + 1] == b'\n' {
-            count += 1;
-            if count == nth {
-                return Some(i);
-            }
-        }
+// `<prefix><len>\r\n`
+fn write_header(buf: &mut BytesMut, prefix: u8, len: usize) {
+    buf.put_u8(prefix);
+    buf.put_slice(itoa::Buffer::new().format(len).as_bytes());
+    buf.put_slice(CRLF);
+}
+
+// The start of the buffer for error messages, it can be arbitrarily large.
+fn preview(buf: &[u8]) -> String {
+    const PREVIEW_LEN: usize = 32;
+    match buf.get(..PREVIEW_LEN) {
+        Some(head) => format!("{}...", String::from_utf8_lossy(head)),
+        None => String::from_utf8_lossy(buf).into_owned(),
     }
-    return None;
+}
+
+// Index of the nth `\r\n`, skipping the type byte.
+fn find_crlf(buf: &[u8], nth: usize) -> Option<usize> {
+    buf.windows(CRLF_LEN)
+        .enumerate()
+        .skip(1)
+        .filter(|(_, w)| *w == CRLF)
+        .nth(nth.checked_sub(1)?)
+        .map(|(i, _)| i)
 }
 
 fn parse_length(buf: &[u8], prefix: &str) -> Result<(usize, usize), RespError> {
@@ -119,36 +172,99 @@ fn parse_length(buf: &[u8], prefix: &str) -> Result<(usize, usize), RespError> {
     Ok((end, total_length.parse::<usize>()?))
 }
 
+// Length of `<prefix><length>\r\n<data>\r\n` given the header's `\r\n` index
+// and `<length>`.
+fn bulk_total_length(end: usize, len: usize) -> Result<usize, RespError> {
+    (end + CRLF_LEN)
+        .checked_add(len)
+        .and_then(|total| total.checked_add(CRLF_LEN))
+        .ok_or(RespError::BulkTooLong)
+}
+
+fn bulk_expect_length(buf: &[u8], prefix: &str) -> Result<usize, RespError> {
+    let (end, len) = parse_length(buf, prefix)?;
+    bulk_total_length(end, len)
+}
+
+// Split `<prefix><length>\r\n<data>\r\n` off the buffer, returning `<data>`.
+fn extract_bulk_data(buf: &mut BytesMut, prefix: &str) -> Result<BytesMut, RespError> {
+    let (end, len) = parse_length(buf, prefix)?;
+    let total = bulk_total_length(end, len)?;
+    if buf.len() < total {
+        return Err(RespError::NotComplete);
+    }
+    if &buf[total - CRLF_LEN..total] != CRLF {
+        return Err(RespError::InvalidFrame(format!(
+            "bulk data is not terminated by CRLF: {}",
+            preview(&buf[end + CRLF_LEN..])
+        )));
+    }
+
+    buf.advance(end + CRLF_LEN);
+    let data = buf.split_to(len);
+    buf.advance(CRLF_LEN);
+    Ok(data)
+}
+
+// Length of the aggregate whose `<prefix><len>\r\n` header ends at `end`.
+// Nested aggregates are walked without recursion, so a deeply nested frame
+// can't overflow the stack.
 fn calculate_total_length(
     buf: &[u8],
     end: usize,
     len: usize,
     prefix: &str,
+    limits: &RespLimits,
 ) -> Result<usize, RespError> {
     let mut total = end + CRLF_LEN;
-    let mut data = &buf[total..];
-
-    match prefix {
-        "*" | "~" => {
-            for _ in 0..len {
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
+    // frames still expected at each nesting level
+    let mut pending = vec![limits.aggregate_frames(prefix.as_bytes()[0], len)?];
+
+    while let Some(remaining) = pending.last_mut() {
+        if *remaining == 0 {
+            pending.pop();
+            continue;
+        }
+        *remaining -= 1;
+
+        let data = buf.get(total..).ok_or(RespError::NotComplete)?;
+        match data.first() {
+            Some(&prefix @ (b'*' | b'~' | b'>' | b'%' | b'|')) => {
+                let (end, len) =
+                    parser::parse_header(data, limits)?.ok_or(RespError::NotComplete)?;
+                total += end + CRLF_LEN;
+                match (prefix, len) {
+                    (b'*', -1) => continue,
+                    (_, len) if len < 0 => return Err(RespError::InvalidFrameLength(len)),
+                    _ => {}
+                }
+                if pending.len() >= limits.max_depth {
+                    return Err(RespError::TooDeep(limits.max_depth));
+                }
+                pending.push(limits.aggregate_frames(prefix, len as usize)?);
+            }
+            _ => {
+                total = total
+                    .checked_add(scalar_length(data, limits)?)
+                    .ok_or(RespError::BulkTooLong)?;
             }
-            Ok(total)
         }
-        "%" => {
-            for _ in 0..len {
-                let len = SimpleString::expect_length(data)?;
-                data = &data[len..];
-                total += len;
-
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
+    }
+    if total > buf.len() {
+        return Err(RespError::NotComplete);
+    }
+    Ok(total)
+}
+
+// Length of a frame that is not an aggregate, bulks longer than
+// `proto-max-bulk-len` are rejected before their data arrived.
+fn scalar_length(buf: &[u8], limits: &RespLimits) -> Result<usize, RespError> {
+    if let Some(b'$' | b'!' | b'=') = buf.first() {
+        if let Some((_, len)) = parser::parse_header(buf, limits)? {
+            if len > 0 && len as usize > limits.max_bulk_len {
+                return Err(RespError::BulkTooLong);
             }
-            Ok(total)
         }
-        _ => Ok(len + CRLF_LEN),
     }
+    RespFrame::expect_length(buf)
 }
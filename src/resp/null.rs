@@ -1,3 +1,5 @@
+use bytes::{BufMut, BytesMut};
+
 use super::{extract_fixed_data, RespDecode, RespEncode, RespError};
 
 // https://redis.io/docs/latest/develop/reference/protocol-spec/#nulls
@@ -6,8 +8,8 @@ pub struct RespNull;
 
 // _\r\n
 impl RespEncode for RespNull {
-    fn encode(self) -> Vec<u8> {
-        b"_\r\n".to_vec()
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.put_slice(b"_\r\n");
     }
 }
 
@@ -0,0 +1,144 @@
+use std::{collections::VecDeque, io::IoSlice};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use super::{write_header, RespEncode, RespFrame, CRLF};
+
+// Bulk payloads at least this big are sent from their own memory rather than
+// being copied into the output buffer.
+const LARGE_BULK_LEN: usize = 16 * 1024;
+
+// Pending output of a connection. Frames are encoded into a buffer, except for
+// large bulk payloads which are queued by reference in between, so a vectored
+// write can send them along with the surrounding protocol bytes.
+#[derive(Debug, Default)]
+pub struct RespOutput {
+    // written out before `buf`
+    chunks: VecDeque<Bytes>,
+    buf: BytesMut,
+}
+
+impl RespOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, frame: &RespFrame) {
+        match frame {
+            RespFrame::BulkString(s) if s.len() >= LARGE_BULK_LEN => {
+                write_header(&mut self.buf, b'$', s.len());
+                self.split_buf();
+                self.chunks.push_back(s.0.clone());
+                self.buf.put_slice(CRLF);
+            }
+            RespFrame::Array(frames) => self.push_all(b'*', &frames.0),
+            RespFrame::Set(frames) => self.push_all(b'~', &frames.0),
+            RespFrame::Push(frames) => self.push_all(b'>', &frames.0),
+            RespFrame::Map(map) => {
+                write_header(&mut self.buf, b'%', map.len());
+                for (key, value) in map.iter() {
+                    self.push(key);
+                    self.push(value);
+                }
+            }
+            frame => frame.encode_to(&mut self.buf),
+        }
+    }
+
+    fn push_all(&mut self, prefix: u8, frames: &[RespFrame]) {
+        write_header(&mut self.buf, prefix, frames.len());
+        for frame in frames {
+            self.push(frame);
+        }
+    }
+
+    fn split_buf(&mut self) {
+        if !self.buf.is_empty() {
+            self.chunks.push_back(self.buf.split().freeze());
+        }
+    }
+}
+
+impl Buf for RespOutput {
+    fn remaining(&self) -> usize {
+        self.chunks.iter().map(Bytes::len).sum::<usize>() + self.buf.len()
+    }
+
+    fn chunk(&self) -> &[u8] {
+        match self.chunks.front() {
+            Some(chunk) => chunk,
+            None => &self.buf,
+        }
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        while let Some(chunk) = self.chunks.front_mut() {
+            if cnt < chunk.len() {
+                chunk.advance(cnt);
+                return;
+            }
+            cnt -= chunk.len();
+            self.chunks.pop_front();
+        }
+        self.buf.advance(cnt);
+    }
+
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        let chunks = self.chunks.iter().map(|chunk| &chunk[..]);
+        let mut n = 0;
+        for (slot, chunk) in dst
+            .iter_mut()
+            .zip(chunks.chain([&self.buf[..]]).filter(|c| !c.is_empty()))
+        {
+            *slot = IoSlice::new(chunk);
+            n += 1;
+        }
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resp::{BulkString, RespArray};
+
+    use super::*;
+
+    #[test]
+    fn test_output_large_bulk() {
+        let value = Bytes::from(vec![b'x'; LARGE_BULK_LEN]);
+        let frame: RespFrame = RespArray::new(vec![
+            BulkString::from("small").into(),
+            BulkString::new(value.clone()).into(),
+            1.into(),
+        ])
+        .into();
+
+        let mut output = RespOutput::new();
+        output.push(&frame);
+
+        let mut slices = [IoSlice::new(&[]); 8];
+        let n = output.chunks_vectored(&mut slices);
+        assert_eq!(n, 3);
+        assert_eq!(&slices[0][..], b"*3\r\n$5\r\nsmall\r\n$16384\r\n");
+        // the payload is not copied
+        assert_eq!(slices[1].as_ptr(), value.as_ptr());
+        assert_eq!(&slices[2][..], b"\r\n:+1\r\n");
+
+        assert_eq!(output.remaining(), frame.encode().len());
+        assert_eq!(output.copy_to_bytes(output.remaining()), frame.encode());
+    }
+
+    #[test]
+    fn test_output_partial_advance() {
+        let mut output = RespOutput::new();
+        output.push(&BulkString::new(vec![b'x'; LARGE_BULK_LEN]).into());
+        output.push(&RespFrame::from(2));
+
+        output.advance(10);
+        assert_eq!(output.chunk(), vec![b'x'; LARGE_BULK_LEN - 2]);
+        output.advance(LARGE_BULK_LEN - 2);
+        assert_eq!(output.chunk(), b"\r\n:+2\r\n");
+        output.advance(7);
+        assert!(!output.has_remaining());
+    }
+}
@@ -0,0 +1,444 @@
+use bytes::{Buf, BytesMut};
+
+use super::{
+    find_crlf, parse_length, RespArray, RespAttribute, RespDecode, RespError, RespFrame, RespMap,
+    RespNull, RespNullArray, RespPush, RespSet, CRLF_LEN,
+};
+
+// Incremental RESP parser. Scalar frames are consumed from the buffer as soon
+// as they are complete and aggregates are kept on a stack until all of their
+// elements arrived, so every byte is parsed once no matter how many reads it
+// takes for a frame to come in.
+#[derive(Debug, Default)]
+pub struct RespParser {
+    stack: Vec<Partial>,
+    limits: RespLimits,
+}
+
+// Bounds on what a peer can make the parser buffer or build, frames exceeding
+// them are protocol errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RespLimits {
+    // proto-max-bulk-len
+    pub max_bulk_len: usize,
+    // proto-max-multibulk-len: elements of an array, set or push, entries of
+    // a map or attribute
+    pub max_multibulk_len: usize,
+    // proto-max-nesting-depth
+    pub max_depth: usize,
+    // simple frames and length headers still missing their CRLF, not
+    // configurable
+    pub max_inline_len: usize,
+}
+
+impl Default for RespLimits {
+    fn default() -> Self {
+        Self {
+            max_bulk_len: 512 * 1024 * 1024,
+            max_multibulk_len: i32::MAX as usize,
+            max_depth: 128,
+            max_inline_len: 64 * 1024,
+        }
+    }
+}
+
+impl RespLimits {
+    // Number of frames an aggregate with header `<prefix><len>` is made of.
+    pub(crate) fn aggregate_frames(&self, prefix: u8, len: usize) -> Result<usize, RespError> {
+        if len > self.max_multibulk_len {
+            return Err(RespError::TooManyElements);
+        }
+        Ok(match prefix {
+            b'%' => len.saturating_mul(2),
+            b'|' => len.saturating_mul(2).saturating_add(1),
+            _ => len,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Partial {
+    kind: Aggregate,
+    remaining: usize,
+    frames: Vec<RespFrame>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aggregate {
+    Array,
+    Set,
+    Push,
+    Map,
+    // the attribute pairs followed by the frame they describe
+    Attribute,
+}
+
+impl RespParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limits(limits: RespLimits) -> Self {
+        Self {
+            stack: Vec::new(),
+            limits,
+        }
+    }
+
+    pub fn limits(&self) -> &RespLimits {
+        &self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: RespLimits) {
+        self.limits = limits;
+    }
+
+    // Whether a frame has been partially consumed from a previous buffer.
+    pub fn is_partial(&self) -> bool {
+        !self.stack.is_empty()
+    }
+
+    // Parse the next frame, `None` means more data is needed. Whatever has
+    // been parsed so far is kept and parsing resumes on the next call, so the
+    // same parser must be fed the rest of the stream.
+    pub fn parse(&mut self, buf: &mut BytesMut) -> Result<Option<RespFrame>, RespError> {
+        let ret = self.parse_frame(buf);
+        if ret.is_err() {
+            // the stream is out of sync, there is nothing to resume
+            self.stack.clear();
+        }
+        ret
+    }
+
+    fn parse_frame(&mut self, buf: &mut BytesMut) -> Result<Option<RespFrame>, RespError> {
+        loop {
+            let (prefix, kind) = match buf.first() {
+                None => return Ok(None),
+                Some(&prefix @ b'*') => (prefix, Aggregate::Array),
+                Some(&prefix @ b'~') => (prefix, Aggregate::Set),
+                Some(&prefix @ b'>') => (prefix, Aggregate::Push),
+                Some(&prefix @ b'%') => (prefix, Aggregate::Map),
+                Some(&prefix @ b'|') => (prefix, Aggregate::Attribute),
+                Some(_) => match self.parse_scalar(buf)? {
+                    Some(frame) => match self.complete(frame) {
+                        Some(frame) => return Ok(Some(frame)),
+                        None => continue,
+                    },
+                    None => return Ok(None),
+                },
+            };
+
+            let Some((end, len)) = parse_header(buf, &self.limits)? else {
+                return Ok(None);
+            };
+            let remaining = match (kind, len) {
+                (Aggregate::Array, -1) => None,
+                (_, len) if len < 0 => return Err(RespError::InvalidFrameLength(len)),
+                (_, len) => Some(self.limits.aggregate_frames(prefix, len as usize)?),
+            };
+            if remaining.is_some() && self.stack.len() >= self.limits.max_depth {
+                return Err(RespError::TooDeep(self.limits.max_depth));
+            }
+            buf.advance(end + CRLF_LEN);
+
+            let Some(remaining) = remaining else {
+                match self.complete(RespNullArray.into()) {
+                    Some(frame) => return Ok(Some(frame)),
+                    None => continue,
+                };
+            };
+
+            let partial = Partial {
+                kind,
+                remaining,
+                // don't trust the peer with the allocation size
+                frames: Vec::with_capacity(remaining.min(1024)),
+            };
+            let frame = if remaining == 0 {
+                partial.finish()
+            } else {
+                self.stack.push(partial);
+                continue;
+            };
+            if let Some(frame) = self.complete(frame) {
+                return Ok(Some(frame));
+            }
+        }
+    }
+
+    fn parse_scalar(&self, buf: &mut BytesMut) -> Result<Option<RespFrame>, RespError> {
+        // reject oversized bulks before buffering them
+        if let Some(b'$' | b'!' | b'=') = buf.first() {
+            if let Some((_, len)) = parse_header(buf, &self.limits)? {
+                if len > 0 && len as usize > self.limits.max_bulk_len {
+                    return Err(RespError::BulkTooLong);
+                }
+            }
+        }
+
+        match RespFrame::decode(buf) {
+            Ok(frame) => Ok(Some(frame)),
+            Err(RespError::NotComplete) => {
+                if find_crlf(buf, 1).is_none() && buf.len() > self.limits.max_inline_len {
+                    return Err(RespError::LineTooLong);
+                }
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // Hand a complete frame to the aggregate being built, returning the
+    // top-level frame once it is done.
+    fn complete(&mut self, mut frame: RespFrame) -> Option<RespFrame> {
+        loop {
+            let Some(partial) = self.stack.last_mut() else {
+                return Some(frame);
+            };
+            partial.frames.push(frame);
+            partial.remaining -= 1;
+            if partial.remaining > 0 {
+                return None;
+            }
+            frame = self.stack.pop()?.finish();
+        }
+    }
+}
+
+impl Partial {
+    fn finish(self) -> RespFrame {
+        match self.kind {
+            Aggregate::Array => RespArray::new(self.frames).into(),
+            Aggregate::Set => RespSet::new(self.frames).into(),
+            Aggregate::Push => RespPush::new(self.frames).into(),
+            Aggregate::Map => RespMap(pairs(self.frames)).into(),
+            Aggregate::Attribute => {
+                let mut frames = self.frames;
+                // `remaining` counted the described frame, it is always there
+                let frame = frames.pop().unwrap_or(RespNull.into());
+                RespAttribute::new(RespMap(pairs(frames)), frame).into()
+            }
+        }
+    }
+}
+
+fn pairs(frames: Vec<RespFrame>) -> Vec<(RespFrame, RespFrame)> {
+    let mut iter = frames.into_iter();
+    let mut pairs = Vec::with_capacity(iter.len() / 2);
+    while let (Some(key), Some(value)) = (iter.next(), iter.next()) {
+        pairs.push((key, value));
+    }
+    pairs
+}
+
+// `<prefix><length>\r\n`, returning the index of the `\r\n` and the length
+pub(crate) fn parse_header(
+    buf: &[u8],
+    limits: &RespLimits,
+) -> Result<Option<(usize, isize)>, RespError> {
+    let Some(end) = find_crlf(buf, 1) else {
+        if buf.len() > limits.max_inline_len {
+            return Err(RespError::LineTooLong);
+        }
+        return Ok(None);
+    };
+    let len = String::from_utf8_lossy(&buf[1..end]).parse::<isize>()?;
+    Ok(Some((end, len)))
+}
+
+// Decode a whole aggregate of type `T` from `buf`, leaving it untouched when
+// the frame is not complete yet. Measuring the frame only scans its headers,
+// once it is all there the parser decodes it in one go and never stops
+// halfway.
+pub(crate) fn decode_aggregate<T: RespDecode>(buf: &mut BytesMut) -> Result<RespFrame, RespError> {
+    let limits = RespLimits::default();
+    // a `T` header, e.g. not the `*-1` of a null array
+    parse_length(buf, T::PREFIX)?;
+    let total_len = RespFrame::expect_length_with_limits(buf, &limits)?;
+    if buf.len() < total_len {
+        return Err(RespError::NotComplete);
+    }
+    RespParser::with_limits(limits)
+        .parse(buf)?
+        .ok_or(RespError::NotComplete)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resp::{BulkString, RespEncode, SimpleString};
+
+    use super::*;
+
+    #[test]
+    fn test_parser_resumes() -> anyhow::Result<()> {
+        let data = b"*3\r\n$3\r\nset\r\n*2\r\n:1\r\n%1\r\n+a\r\n#t\r\n$5\r\nhello\r\n+OK\r\n";
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::new();
+
+        // feed the frames one byte at a time
+        let mut frames = vec![];
+        for b in data {
+            buf.extend_from_slice(&[*b]);
+            if let Some(frame) = parser.parse(&mut buf)? {
+                frames.push(frame);
+            }
+        }
+        assert!(buf.is_empty());
+        assert!(!parser.is_partial());
+
+        let mut map = RespMap::new();
+        map.insert(SimpleString::new("a"), true);
+        let expected: RespFrame = RespArray::new(vec![
+            BulkString::new("set").into(),
+            RespArray::new(vec![1.into(), map.into()]).into(),
+            BulkString::new("hello").into(),
+        ])
+        .into();
+        assert_eq!(frames, vec![expected, SimpleString::new("OK").into()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_partial_state() -> anyhow::Result<()> {
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::from(&b"*2\r\n$3\r\nget\r\n$3\r\nk"[..]);
+        assert_eq!(parser.parse(&mut buf)?, None);
+        // the complete element has been consumed, the partial one is left
+        assert!(parser.is_partial());
+        assert_eq!(&buf[..], b"$3\r\nk");
+
+        buf.extend_from_slice(b"ey\r\n");
+        let frame = parser.parse(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(
+                RespArray::new(vec![
+                    BulkString::new("get").into(),
+                    BulkString::new("key").into()
+                ])
+                .into()
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_empty_and_null() -> anyhow::Result<()> {
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::from(&b"*2\r\n*0\r\n*-1\r\n%0\r\n|1\r\n+ttl\r\n:10\r\n~0\r\n"[..]);
+        let frame = parser.parse(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(RespArray::new(vec![RespArray::new(vec![]).into(), RespNullArray.into()]).into())
+        );
+        assert_eq!(parser.parse(&mut buf)?, Some(RespMap::new().into()));
+
+        let mut attributes = RespMap::new();
+        attributes.insert(SimpleString::new("ttl"), 10);
+        let frame = parser.parse(&mut buf)?.expect("attribute");
+        assert_eq!(
+            frame,
+            RespFrame::from(RespSet::new(vec![])).with_attributes(attributes)
+        );
+        assert_eq!(frame.encode(), b"|1\r\n+ttl\r\n:+10\r\n~0\r\n".to_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parser_error_resets() {
+        let mut parser = RespParser::new();
+        let mut buf = BytesMut::from(&b"*2\r\n:1\r\n*-2\r\n"[..]);
+        assert_eq!(
+            parser.parse(&mut buf),
+            Err(RespError::InvalidFrameLength(-2))
+        );
+        assert!(!parser.is_partial());
+    }
+
+    #[test]
+    fn test_parser_limits() {
+        let limits = RespLimits {
+            max_bulk_len: 10,
+            max_multibulk_len: 4,
+            max_depth: 2,
+            max_inline_len: 16,
+        };
+        let parse = |data: &[u8]| {
+            let mut parser = RespParser::with_limits(limits);
+            parser.parse(&mut BytesMut::from(data))
+        };
+
+        // rejected as soon as the header is in, without waiting for the data
+        assert_eq!(parse(b"$11\r\n"), Err(RespError::BulkTooLong));
+        assert_eq!(parse(b"*1\r\n!11\r\n"), Err(RespError::BulkTooLong));
+        assert_eq!(parse(b"$10\r\n"), Ok(None));
+        assert_eq!(parse(b"*5\r\n"), Err(RespError::TooManyElements));
+        assert_eq!(parse(b"%5\r\n"), Err(RespError::TooManyElements));
+        assert_eq!(parse(b"*1\r\n*1\r\n*1\r\n"), Err(RespError::TooDeep(2)));
+        assert_eq!(parse(b"*1\r\n*-1\r\n").map(|f| f.is_some()), Ok(true));
+        assert_eq!(parse(&[b'+'; 17]), Err(RespError::LineTooLong));
+        assert_eq!(parse(&[b'*'; 17]), Err(RespError::LineTooLong));
+        assert_eq!(parse(&[b'+'; 16]), Ok(None));
+
+        // measuring a frame applies the same limits
+        let length = |data: &[u8]| RespFrame::expect_length_with_limits(data, &limits);
+        assert_eq!(length(b"$11\r\n"), Err(RespError::BulkTooLong));
+        assert_eq!(length(b"*1\r\n!11\r\n"), Err(RespError::BulkTooLong));
+        assert_eq!(length(b"*5\r\n"), Err(RespError::TooManyElements));
+        assert_eq!(length(b"*1\r\n*1\r\n*1\r\n"), Err(RespError::TooDeep(2)));
+        assert_eq!(length(b"*1\r\n$10\r\n0123456789\r\n"), Ok(21));
+        assert_eq!(
+            RespFrame::expect_length(b"*1\r\n*1\r\n*1\r\n"),
+            Err(RespError::NotComplete)
+        );
+    }
+
+    #[test]
+    fn test_decode_deep_nesting() {
+        let mut buf = BytesMut::from(&b"*1\r\n".repeat(100_000)[..]);
+        assert_eq!(
+            RespFrame::expect_length(&buf),
+            Err(RespError::TooDeep(RespLimits::default().max_depth))
+        );
+        assert_eq!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::TooDeep(RespLimits::default().max_depth))
+        );
+    }
+
+    #[test]
+    fn test_decode_arbitrary_input() {
+        let samples: [&[u8]; 8] = [
+            b"*3\r\n$3\r\nset\r\n$1\r\nk\r\n$-1\r\n",
+            b"%2\r\n+a\r\n:1\r\n$1\r\nb\r\n~1\r\n#t\r\n",
+            b"|1\r\n+ttl\r\n,1.5\r\n>2\r\n(123\r\n_\r\n",
+            b"=15\r\ntxt:Some string\r\n",
+            b"!5\r\nERR x\r\n",
+            b"*-1\r\n",
+            b"-ERR oops\r\n",
+            b"$0\r\n\r\n",
+        ];
+        let bytes = [b'\r', b'\n', b'-', b'*', b'%', b'|', b'$', b'0', b'9', 0xff];
+
+        let check = |data: &[u8]| {
+            let _ = RespFrame::expect_length(data);
+            let _ = RespFrame::decode(&mut BytesMut::from(data));
+            let mut parser = RespParser::new();
+            let mut buf = BytesMut::from(data);
+            while let Ok(Some(_)) = parser.parse(&mut buf) {}
+        };
+
+        for sample in samples {
+            for i in 0..=sample.len() {
+                check(&sample[..i]);
+            }
+            for i in 0..sample.len() {
+                for b in bytes {
+                    let mut data = sample.to_vec();
+                    data[i] = b;
+                    check(&data);
+                }
+            }
+        }
+    }
+}
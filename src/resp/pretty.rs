@@ -0,0 +1,211 @@
+use std::fmt::{self, Write};
+
+use super::{double::format_double, RespFrame, RespMap};
+
+impl RespFrame {
+    // Human readable rendering in the style of redis-cli, for logs and tests:
+    //
+    //     1) "foo"
+    //     2) (integer) 1
+    //     3) 1) (nil)
+    //        2) (error) ERR oops
+    pub fn pretty(&self) -> Pretty<'_> {
+        Pretty(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Pretty<'a>(&'a RespFrame);
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_frame(f, self.0, 0)
+    }
+}
+
+// `indent` is the column nested lines start at.
+fn write_frame(f: &mut fmt::Formatter<'_>, frame: &RespFrame, indent: usize) -> fmt::Result {
+    match frame {
+        RespFrame::SimpleString(s) => f.write_str(s),
+        RespFrame::Error(e) => write!(f, "(error) {}", &e[..]),
+        RespFrame::BulkError(e) => write!(f, "(error) {}", &e[..]),
+        RespFrame::Integer(n) => write!(f, "(integer) {}", n),
+        RespFrame::Double(d) => write!(f, "(double) {}", format_double(*d)),
+        RespFrame::Boolean(b) => write!(f, "({})", b),
+        RespFrame::BigNumber(n) => write!(f, "(big number) {}", &n[..]),
+        RespFrame::BulkString(s) => write_quoted(f, s),
+        RespFrame::VerbatimString(s) => f.write_str(&String::from_utf8_lossy(s.data())),
+        RespFrame::NullBulkString(_) | RespFrame::NullArray(_) | RespFrame::Null(_) => {
+            f.write_str("(nil)")
+        }
+        RespFrame::Array(frames) => write_items(f, frames, ')', "(empty array)", indent),
+        RespFrame::Push(frames) => write_items(f, frames, ')', "(empty array)", indent),
+        RespFrame::Set(frames) => write_items(f, frames, '~', "(empty set)", indent),
+        RespFrame::Map(map) => write_entries(f, map, '#', "(empty hash)", indent),
+        RespFrame::Attribute(attr) => {
+            write_entries(f, attr.attributes(), '|', "(empty attributes)", indent)?;
+            write!(f, "\n{:indent$}", "")?;
+            write_frame(f, attr.frame(), indent)
+        }
+    }
+}
+
+fn write_items(
+    f: &mut fmt::Formatter<'_>,
+    frames: &[RespFrame],
+    marker: char,
+    empty: &str,
+    indent: usize,
+) -> fmt::Result {
+    if frames.is_empty() {
+        return f.write_str(empty);
+    }
+    let width = frames.len().to_string().len();
+    for (i, frame) in frames.iter().enumerate() {
+        let nested = write_index(f, i, width, marker, indent)?;
+        write_frame(f, frame, nested)?;
+    }
+    Ok(())
+}
+
+fn write_entries(
+    f: &mut fmt::Formatter<'_>,
+    map: &RespMap,
+    marker: char,
+    empty: &str,
+    indent: usize,
+) -> fmt::Result {
+    if map.is_empty() {
+        return f.write_str(empty);
+    }
+    let width = map.len().to_string().len();
+    for (i, (key, value)) in map.iter().enumerate() {
+        let nested = write_index(f, i, width, marker, indent)?;
+        write_frame(f, key, nested)?;
+        f.write_str(" => ")?;
+        write_frame(f, value, nested)?;
+    }
+    Ok(())
+}
+
+// Writes the `N) ` label of the ith item, right aligned to `width` digits,
+// and returns the column its content starts at.
+fn write_index(
+    f: &mut fmt::Formatter<'_>,
+    i: usize,
+    width: usize,
+    marker: char,
+    indent: usize,
+) -> Result<usize, fmt::Error> {
+    if i > 0 {
+        write!(f, "\n{:indent$}", "")?;
+    }
+    write!(f, "{:>width$}{} ", i + 1, marker)?;
+    Ok(indent + width + 2)
+}
+
+// Quote and escape like redis-cli (sdscatrepr).
+fn write_quoted(f: &mut fmt::Formatter<'_>, s: &[u8]) -> fmt::Result {
+    f.write_char('"')?;
+    for &b in s {
+        match b {
+            b'\\' => f.write_str("\\\\")?,
+            b'"' => f.write_str("\\\"")?,
+            b'\n' => f.write_str("\\n")?,
+            b'\r' => f.write_str("\\r")?,
+            b'\t' => f.write_str("\\t")?,
+            0x07 => f.write_str("\\a")?,
+            0x08 => f.write_str("\\b")?,
+            b' '..=b'~' => f.write_char(b as char)?,
+            b => write!(f, "\\x{:02x}", b)?,
+        }
+    }
+    f.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resp::{
+        BigNumber, BulkError, BulkString, RespArray, RespAttribute, RespNull, RespNullBulkString,
+        RespSet, SimpleError, SimpleString, VerbatimString,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_pretty_scalars() {
+        let cases: Vec<(RespFrame, &str)> = vec![
+            (SimpleString::new("OK").into(), "OK"),
+            (SimpleError::new("ERR oops").into(), "(error) ERR oops"),
+            (BulkError::new("SYNTAX bad").into(), "(error) SYNTAX bad"),
+            (RespFrame::Integer(-3), "(integer) -3"),
+            (RespFrame::Double(1.5), "(double) 1.5"),
+            (RespFrame::Double(f64::NEG_INFINITY), "(double) -inf"),
+            (true.into(), "(true)"),
+            (
+                BigNumber::new("-12345678901234567890").unwrap().into(),
+                "(big number) -12345678901234567890",
+            ),
+            (
+                BulkString::from("a \"b\"\n\x01\u{e9}").into(),
+                r#""a \"b\"\n\x01\xc3\xa9""#,
+            ),
+            (VerbatimString::txt("line1\nline2").into(), "line1\nline2"),
+            (RespNullBulkString.into(), "(nil)"),
+            (RespNull.into(), "(nil)"),
+        ];
+        for (frame, expected) in cases {
+            assert_eq!(frame.pretty().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_pretty_aggregates() {
+        let frame: RespFrame = RespArray::new(vec![
+            BulkString::from("foo").into(),
+            RespFrame::Integer(1),
+            RespArray::new(vec![RespNull.into(), SimpleError::new("ERR oops").into()]).into(),
+            RespArray::new(vec![]).into(),
+            RespSet::new(vec![]).into(),
+        ])
+        .into();
+        let expected = "\
+1) \"foo\"
+2) (integer) 1
+3) 1) (nil)
+   2) (error) ERR oops
+4) (empty array)
+5) (empty set)";
+        assert_eq!(frame.pretty().to_string(), expected);
+
+        // indexes are right aligned
+        let frame: RespFrame =
+            RespArray::new((1..=10).map(RespFrame::Integer).collect::<Vec<_>>()).into();
+        let pretty = frame.pretty().to_string();
+        assert!(pretty.starts_with(" 1) (integer) 1\n 2) (integer) 2\n"));
+        assert!(pretty.ends_with("\n10) (integer) 10"));
+
+        let mut map = RespMap::new();
+        map.insert(BulkString::from("server"), BulkString::from("redis"));
+        map.insert(
+            BulkString::from("modules"),
+            RespArray::new(vec![
+                BulkString::from("a").into(),
+                BulkString::from("b").into(),
+            ]),
+        );
+        let expected = "\
+1# \"server\" => \"redis\"
+2# \"modules\" => 1) \"a\"
+   2) \"b\"";
+        assert_eq!(RespFrame::from(map.clone()).pretty().to_string(), expected);
+
+        let mut attributes = RespMap::new();
+        attributes.insert(BulkString::from("ttl"), RespFrame::Integer(3));
+        let frame: RespFrame = RespAttribute::new(attributes, BulkString::from("v")).into();
+        assert_eq!(
+            frame.pretty().to_string(),
+            "1| \"ttl\" => (integer) 3\n\"v\""
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::ops::Deref;
+
+use bytes::BytesMut;
+
+use super::{
+    calculate_total_length, decode_aggregate, parse_length, write_header, RespDecode, RespEncode,
+    RespError, RespFrame, RespLimits,
+};
+
+// Out-of-band data such as pub/sub messages and client side caching
+// invalidations, which must not be mistaken for the reply to a request.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct RespPush(pub(crate) Vec<RespFrame>);
+
+// https://redis.io/docs/latest/develop/reference/protocol-spec/#pushes
+// `><number-of-elements>\r\n<element-1>...<element-n>`
+impl RespEncode for RespPush {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        write_header(buf, b'>', self.0.len());
+        for frame in &self.0 {
+            frame.encode_to(buf);
+        }
+    }
+}
+
+impl RespDecode for RespPush {
+    const PREFIX: &'static str = ">";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        match decode_aggregate::<Self>(buf)? {
+            RespFrame::Push(frame) => Ok(frame),
+            frame => Err(RespError::InvalidFrameType(format!(
+                "expect: Push, actual: {:?}",
+                frame
+            ))),
+        }
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        let (end, len) = parse_length(buf, Self::PREFIX)?;
+        calculate_total_length(buf, end, len, Self::PREFIX, &RespLimits::default())
+    }
+}
+
+impl RespPush {
+    pub fn new(frames: impl Into<Vec<RespFrame>>) -> Self {
+        RespPush(frames.into())
+    }
+
+    // The push type, e.g. `message` or `invalidate`.
+    pub fn kind(&self) -> Option<&[u8]> {
+        match self.0.first() {
+            Some(RespFrame::BulkString(s)) => Some(s.as_ref()),
+            Some(RespFrame::SimpleString(s)) => Some(s.as_bytes()),
+            _ => None,
+        }
+    }
+}
+
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<RespFrame>> for RespPush {
+    fn from(value: Vec<RespFrame>) -> Self {
+        RespPush(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::resp::{BulkString, RespArray};
+
+    use super::*;
+
+    #[test]
+    fn test_push_encode() {
+        let frame: RespFrame = RespPush::new(vec![
+            BulkString::new("message").into(),
+            BulkString::new("news").into(),
+            BulkString::new("hi").into(),
+        ])
+        .into();
+
+        assert_eq!(
+            &frame.encode(),
+            b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$2\r\nhi\r\n"
+        );
+    }
+
+    #[test]
+    fn test_push_decode() -> anyhow::Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nfoo\r\n");
+
+        let push = RespPush::decode(&mut buf)?;
+        assert_eq!(push.kind(), Some(&b"invalidate"[..]));
+        assert_eq!(
+            push,
+            RespPush::new(vec![
+                BulkString::new("invalidate").into(),
+                RespArray::new(vec![BulkString::new("foo").into()]).into(),
+            ])
+        );
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_not_complete() {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nfo");
+
+        assert_eq!(RespPush::expect_length(&buf), Err(RespError::NotComplete));
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::NotComplete));
+        buf.extend_from_slice(b"o\r\n");
+        assert_eq!(RespFrame::expect_length(&buf), Ok(buf.len()));
+        assert!(matches!(
+            RespFrame::decode(&mut buf),
+            Ok(RespFrame::Push(_))
+        ));
+    }
+}
@@ -0,0 +1,362 @@
+use std::{str::FromStr, vec};
+
+use serde::{
+    de::{self, DeserializeOwned, IntoDeserializer, Visitor},
+    forward_to_deserialize_any,
+};
+
+use crate::resp::RespFrame;
+
+use super::SerdeError;
+
+pub fn from_frame<T: DeserializeOwned>(frame: RespFrame) -> Result<T, SerdeError> {
+    T::deserialize(Deserializer::new(frame))
+}
+
+// Deserializes from an owned `RespFrame`. Numbers may also be read from
+// strings, since that's how values stored in Redis come back, and error
+// replies fail with `SerdeError::ErrorReply`.
+#[derive(Debug)]
+pub struct Deserializer {
+    frame: RespFrame,
+}
+
+impl Deserializer {
+    pub fn new(frame: RespFrame) -> Self {
+        Self {
+            // attributes are metadata, not part of the value
+            frame: frame.into_inner(),
+        }
+    }
+
+    fn parse<T: FromStr>(self, expected: &'static str) -> Result<T, SerdeError> {
+        let s = match &self.frame {
+            RespFrame::SimpleString(s) => s.as_bytes(),
+            RespFrame::BulkString(s) => &s[..],
+            RespFrame::BigNumber(n) => n.as_bytes(),
+            _ => return Err(self.unexpected()),
+        };
+        std::str::from_utf8(s)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| SerdeError::Message(format!("invalid {}: {:?}", expected, self.frame)))
+    }
+
+    fn unexpected(self) -> SerdeError {
+        match self.frame {
+            RespFrame::Error(e) => SerdeError::ErrorReply(e.to_string()),
+            RespFrame::BulkError(e) => SerdeError::ErrorReply(e.to_string()),
+            frame => SerdeError::UnexpectedFrame(frame_type(&frame)),
+        }
+    }
+}
+
+fn frame_type(frame: &RespFrame) -> &'static str {
+    match frame {
+        RespFrame::SimpleString(_) => "simple string",
+        RespFrame::Error(_) => "error",
+        RespFrame::Integer(_) => "integer",
+        RespFrame::Double(_) => "double",
+        RespFrame::Boolean(_) => "boolean",
+        RespFrame::Map(_) => "map",
+        RespFrame::Set(_) => "set",
+        RespFrame::BulkString(_) => "bulk string",
+        RespFrame::NullBulkString(_) | RespFrame::NullArray(_) | RespFrame::Null(_) => "null",
+        RespFrame::Array(_) => "array",
+        RespFrame::Push(_) => "push",
+        RespFrame::BigNumber(_) => "big number",
+        RespFrame::VerbatimString(_) => "verbatim string",
+        RespFrame::BulkError(_) => "bulk error",
+        RespFrame::Attribute(_) => "attribute",
+    }
+}
+
+fn is_null(frame: &RespFrame) -> bool {
+    matches!(
+        frame,
+        RespFrame::Null(_) | RespFrame::NullBulkString(_) | RespFrame::NullArray(_)
+    )
+}
+
+// Integers are deserialized from integer and big number frames, or strings.
+macro_rules! deserialize_integer {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+                match self.frame {
+                    RespFrame::Integer(n) => visitor.visit_i64(n),
+                    _ => visitor.$visit(self.parse("integer")?),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.frame {
+            RespFrame::SimpleString(s) => visitor.visit_string(s.0),
+            RespFrame::BulkString(s) => match std::str::from_utf8(&s) {
+                Ok(s) => visitor.visit_str(s),
+                Err(_) => visitor.visit_bytes(&s),
+            },
+            RespFrame::VerbatimString(s) => match std::str::from_utf8(s.data()) {
+                Ok(s) => visitor.visit_str(s),
+                Err(_) => visitor.visit_bytes(s.data()),
+            },
+            RespFrame::Integer(n) => visitor.visit_i64(n),
+            RespFrame::Double(n) => visitor.visit_f64(n),
+            RespFrame::Boolean(b) => visitor.visit_bool(b),
+            RespFrame::BigNumber(n) => match (n.parse::<i128>(), n.parse::<u128>()) {
+                (Ok(n), _) => visitor.visit_i128(n),
+                (_, Ok(n)) => visitor.visit_u128(n),
+                _ => visitor.visit_str(&n),
+            },
+            RespFrame::Array(frames) => visitor.visit_seq(SeqAccess::new(frames.0)),
+            RespFrame::Set(frames) => visitor.visit_seq(SeqAccess::new(frames.0)),
+            RespFrame::Push(frames) => visitor.visit_seq(SeqAccess::new(frames.0)),
+            RespFrame::Map(map) => visitor.visit_map(MapAccess::new(map.0)),
+            frame if is_null(&frame) => visitor.visit_unit(),
+            frame => Err(Deserializer { frame }.unexpected()),
+        }
+    }
+
+    deserialize_integer! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.frame {
+            RespFrame::Double(n) => visitor.visit_f64(n),
+            RespFrame::Integer(n) => visitor.visit_f64(n as f64),
+            _ => visitor.visit_f64(self.parse("double")?),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.frame {
+            RespFrame::BulkString(s) => visitor.visit_bytes(&s),
+            RespFrame::SimpleString(s) => visitor.visit_bytes(s.as_bytes()),
+            RespFrame::VerbatimString(s) => visitor.visit_bytes(s.data()),
+            frame => Deserializer { frame }.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if is_null(&self.frame) {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.frame {
+            RespFrame::BulkString(_) | RespFrame::SimpleString(_) => {
+                visitor.visit_enum(EnumAccess {
+                    variant: self.frame,
+                    content: None,
+                })
+            }
+            RespFrame::Map(map) if map.len() == 1 => {
+                let (variant, content) = map.0.into_iter().next().expect("one entry");
+                visitor.visit_enum(EnumAccess {
+                    variant,
+                    content: Some(content),
+                })
+            }
+            frame => Err(Deserializer { frame }.unexpected()),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool char str string unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct SeqAccess {
+    frames: vec::IntoIter<RespFrame>,
+}
+
+impl SeqAccess {
+    fn new(frames: Vec<RespFrame>) -> Self {
+        Self {
+            frames: frames.into_iter(),
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        self.frames
+            .next()
+            .map(|frame| seed.deserialize(Deserializer::new(frame)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.frames.len())
+    }
+}
+
+struct MapAccess {
+    entries: vec::IntoIter<(RespFrame, RespFrame)>,
+    value: Option<RespFrame>,
+}
+
+impl MapAccess {
+    fn new(entries: Vec<(RespFrame, RespFrame)>) -> Self {
+        Self {
+            entries: entries.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(Deserializer::new(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| SerdeError::Message("map key without a value".to_string()))?;
+        seed.deserialize(Deserializer::new(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct EnumAccess {
+    variant: RespFrame,
+    content: Option<RespFrame>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+    type Error = SerdeError;
+    type Variant = VariantAccess;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantAccess), SerdeError> {
+        let variant = seed.deserialize(Deserializer::new(self.variant))?;
+        Ok((
+            variant,
+            VariantAccess {
+                content: self.content,
+            },
+        ))
+    }
+}
+
+struct VariantAccess {
+    content: Option<RespFrame>,
+}
+
+impl VariantAccess {
+    fn content(self) -> Result<Deserializer, SerdeError> {
+        self.content
+            .map(Deserializer::new)
+            .ok_or_else(|| SerdeError::Message("expected a variant with content".to_string()))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.content {
+            None => Ok(()),
+            Some(frame) if is_null(&frame) => Ok(()),
+            Some(frame) => Err(SerdeError::UnexpectedFrame(frame_type(&frame))),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self.content()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_seq(self.content()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_map(self.content()?, visitor)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, SerdeError> for RespFrame {
+    type Deserializer = Deserializer;
+
+    fn into_deserializer(self) -> Deserializer {
+        Deserializer::new(self)
+    }
+}
@@ -0,0 +1,171 @@
+// Serde support, converting Rust values to and from `RespFrame` trees.
+// Structs and maps become RESP3 maps keyed by bulk strings, sequences and
+// tuples arrays, `None` and `()` null and floats doubles.
+mod de;
+mod ser;
+
+use std::fmt::Display;
+
+use thiserror::Error;
+
+pub use de::{from_frame, Deserializer};
+pub use ser::{to_frame, Serializer};
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SerdeError {
+    #[error("{0}")]
+    Message(String),
+    #[error("unexpected {0} frame")]
+    UnexpectedFrame(&'static str),
+    #[error("error reply: {0}")]
+    ErrorReply(String),
+}
+
+impl serde::ser::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Message(msg.to_string())
+    }
+}
+
+impl serde::de::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Message(msg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bytes::BytesMut;
+    use serde::{Deserialize, Serialize};
+
+    use crate::resp::{
+        BigNumber, BulkString, RespArray, RespDecode, RespEncode, RespFrame, RespMap, RespNull,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Role {
+        Admin,
+        Guest(String),
+        Member { since: u32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        id: u64,
+        name: String,
+        score: f64,
+        email: Option<String>,
+        tags: Vec<String>,
+        roles: Vec<Role>,
+        limits: BTreeMap<String, i32>,
+        pair: (bool, i8),
+    }
+
+    fn user() -> User {
+        User {
+            id: 42,
+            name: "alice".to_string(),
+            score: 1.5,
+            email: None,
+            tags: vec!["a".to_string(), "b".to_string()],
+            roles: vec![
+                Role::Admin,
+                Role::Guest("bob".to_string()),
+                Role::Member { since: 2020 },
+            ],
+            limits: BTreeMap::from([("rate".to_string(), -1)]),
+            pair: (true, 3),
+        }
+    }
+
+    #[test]
+    fn test_to_frame() -> anyhow::Result<()> {
+        let frame = to_frame(&user())?;
+        let RespFrame::Map(map) = &frame else {
+            panic!("expected a map: {:?}", frame);
+        };
+
+        // fields keep their declaration order
+        let keys: Vec<RespFrame> = map.iter().map(|(k, _)| k.clone()).collect();
+        let expected: Vec<RespFrame> = [
+            "id", "name", "score", "email", "tags", "roles", "limits", "pair",
+        ]
+        .into_iter()
+        .map(|k| BulkString::from(k).into())
+        .collect();
+        assert_eq!(keys, expected);
+
+        assert_eq!(map.get_str("id"), Some(&RespFrame::Integer(42)));
+        assert_eq!(map.get_str("score"), Some(&RespFrame::Double(1.5)));
+        assert_eq!(map.get_str("email"), Some(&RespNull.into()));
+        assert_eq!(
+            map.get_str("tags"),
+            Some(
+                &RespArray::new(vec![
+                    BulkString::from("a").into(),
+                    BulkString::from("b").into()
+                ])
+                .into()
+            )
+        );
+
+        let mut guest = RespMap::new();
+        guest.insert(BulkString::from("Guest"), BulkString::from("bob"));
+        let RespFrame::Array(roles) = map.get_str("roles").unwrap() else {
+            panic!("expected an array");
+        };
+        assert_eq!(roles[0], BulkString::from("Admin").into());
+        assert_eq!(roles[1], guest.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() -> anyhow::Result<()> {
+        let user = user();
+        assert_eq!(from_frame::<User>(to_frame(&user)?)?, user);
+
+        // through the wire format as well
+        let mut buf = BytesMut::from(&to_frame(&user)?.encode()[..]);
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(from_frame::<User>(frame)?, user);
+
+        let values: Vec<Option<i64>> = vec![Some(1), None, Some(-1)];
+        assert_eq!(from_frame::<Vec<Option<i64>>>(to_frame(&values)?)?, values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_frame_conversions() -> anyhow::Result<()> {
+        // hash fields come back as bulk strings
+        let mut map = RespMap::new();
+        map.insert(BulkString::from("id"), BulkString::from("7"));
+        map.insert(BulkString::from("score"), BulkString::from("0.5"));
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Entry {
+            id: u64,
+            score: f64,
+        }
+        assert_eq!(
+            from_frame::<Entry>(map.into())?,
+            Entry { id: 7, score: 0.5 }
+        );
+
+        let big = BigNumber::new("170141183460469231731687303715884105727")?;
+        assert_eq!(from_frame::<i128>(big.into())?, i128::MAX);
+        assert_eq!(
+            to_frame(&u64::MAX)?,
+            BigNumber::from(u64::MAX as i128).into()
+        );
+        assert_eq!(from_frame::<u64>(to_frame(&u64::MAX)?)?, u64::MAX);
+
+        assert_eq!(
+            from_frame::<String>(crate::resp::SimpleError::new("ERR nope").into()),
+            Err(SerdeError::ErrorReply("ERR nope".to_string()))
+        );
+        Ok(())
+    }
+}
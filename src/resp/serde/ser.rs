@@ -0,0 +1,359 @@
+use serde::{ser, Serialize};
+
+use crate::resp::{BigNumber, BulkString, RespArray, RespFrame, RespMap, RespNull};
+
+use super::SerdeError;
+
+pub fn to_frame<T: Serialize + ?Sized>(value: &T) -> Result<RespFrame, SerdeError> {
+    value.serialize(Serializer)
+}
+
+// Serializes into a `RespFrame` tree. Enum variants follow serde's externally
+// tagged representation: unit variants are their name, other variants a single
+// entry map from the name to the content.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Serializer;
+
+fn integer(n: i128) -> RespFrame {
+    match i64::try_from(n) {
+        Ok(n) => n.into(),
+        Err(_) => BigNumber::from(n).into(),
+    }
+}
+
+fn variant(name: &str, content: impl Into<RespFrame>) -> RespFrame {
+    let mut map = RespMap::with_capacity(1);
+    map.push(BulkString::from(name), content);
+    map.into()
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<RespFrame, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<RespFrame, SerdeError> {
+        Ok(RespFrame::Integer(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<RespFrame, SerdeError> {
+        Ok(RespFrame::Integer(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<RespFrame, SerdeError> {
+        Ok(RespFrame::Integer(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<RespFrame, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<RespFrame, SerdeError> {
+        Ok(integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<RespFrame, SerdeError> {
+        Ok(RespFrame::Integer(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<RespFrame, SerdeError> {
+        Ok(RespFrame::Integer(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<RespFrame, SerdeError> {
+        Ok(RespFrame::Integer(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<RespFrame, SerdeError> {
+        Ok(integer(v.into()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<RespFrame, SerdeError> {
+        match i128::try_from(v) {
+            Ok(v) => Ok(integer(v)),
+            Err(_) => Ok(BigNumber(v.to_string()).into()),
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<RespFrame, SerdeError> {
+        Ok(RespFrame::Double(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<RespFrame, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<RespFrame, SerdeError> {
+        Ok(BulkString::from(v.to_string()).into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<RespFrame, SerdeError> {
+        Ok(BulkString::from(v).into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<RespFrame, SerdeError> {
+        Ok(BulkString::from(v).into())
+    }
+
+    fn serialize_none(self) -> Result<RespFrame, SerdeError> {
+        Ok(RespNull.into())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<RespFrame, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<RespFrame, SerdeError> {
+        Ok(RespNull.into())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<RespFrame, SerdeError> {
+        Ok(RespNull.into())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<RespFrame, SerdeError> {
+        Ok(BulkString::from(variant).into())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<RespFrame, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        value: &T,
+    ) -> Result<RespFrame, SerdeError> {
+        Ok(variant(name, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            variant: None,
+            frames: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            variant: Some(name),
+            frames: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap {
+            variant: None,
+            map: RespMap::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap {
+            variant: Some(name),
+            map: RespMap::with_capacity(len),
+            key: None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct SerializeArray {
+    variant: Option<&'static str>,
+    frames: Vec<RespFrame>,
+}
+
+impl SerializeArray {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.frames.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> RespFrame {
+        let array = RespArray::new(self.frames);
+        match self.variant {
+            Some(name) => variant(name, array),
+            None => array.into(),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+#[derive(Debug)]
+pub struct SerializeMap {
+    variant: Option<&'static str>,
+    map: RespMap,
+    // set between `serialize_key` and `serialize_value`
+    key: Option<RespFrame>,
+}
+
+impl SerializeMap {
+    fn finish(self) -> RespFrame {
+        match self.variant {
+            Some(name) => variant(name, self.map),
+            None => self.map.into(),
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(key.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerdeError::Message("map value without a key".to_string()))?;
+        // the keys of a serialized map are already unique
+        self.map.push(key, value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.map
+            .push(BulkString::from(key), value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        Ok(self.finish())
+    }
+}
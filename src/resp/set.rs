@@ -1,10 +1,10 @@
 use std::ops::Deref;
 
-use bytes::Buf;
+use bytes::BytesMut;
 
 use super::{
-    calculate_total_length, parse_length, RespDecode, RespEncode, RespError, RespFrame, BUF_CAP,
-    CRLF_LEN,
+    calculate_total_length, decode_aggregate, parse_length, write_header, RespDecode, RespEncode,
+    RespError, RespFrame, RespLimits,
 };
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
@@ -13,40 +13,30 @@ pub struct RespSet(pub(crate) Vec<RespFrame>);
 // https://redis.io/docs/latest/develop/reference/protocol-spec/#sets
 // RespSet encoding is: `~<number-of-elements>\r\n<element-1>...<element-n>`
 impl RespEncode for RespSet {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(BUF_CAP);
-        buf.extend_from_slice(&format!("~{}\r\n", self.len()).into_bytes());
-
-        for frame in self.0 {
-            buf.extend_from_slice(&frame.encode())
+    fn encode_to(&self, buf: &mut BytesMut) {
+        write_header(buf, b'~', self.0.len());
+        for frame in &self.0 {
+            frame.encode_to(buf);
         }
-        buf
     }
 }
 
 impl RespDecode for RespSet {
     const PREFIX: &'static str = "~";
-    fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let total_len = calculate_total_length(buf, end, len, Self::PREFIX)?;
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
-        }
-
-        buf.advance(end + CRLF_LEN);
-
-        let mut frames = vec![];
-        for _ in 0..len {
-            let frame = RespFrame::decode(buf)?;
-            frames.push(frame);
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        match decode_aggregate::<Self>(buf)? {
+            RespFrame::Set(frame) => Ok(frame),
+            frame => Err(RespError::InvalidFrameType(format!(
+                "expect: Set, actual: {:?}",
+                frame
+            ))),
         }
-
-        Ok(RespSet::new(frames))
     }
 
     fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
         let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let total_len = calculate_total_length(buf, end, len, Self::PREFIX)?;
+        let total_len =
+            calculate_total_length(buf, end, len, Self::PREFIX, &RespLimits::default())?;
         Ok(total_len)
     }
 }
@@ -1,13 +1,17 @@
 use std::ops::Deref;
 
-use super::{extract_simple_frame_data, RespDecode, RespEncode, RespError, CRLF_LEN};
+use bytes::{BufMut, BytesMut};
+
+use super::{extract_simple_frame_data, RespDecode, RespEncode, RespError, CRLF, CRLF_LEN};
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct SimpleError(pub(crate) String);
 
 impl RespEncode for SimpleError {
-    fn encode(self) -> Vec<u8> {
-        format!("-{}\r\n", self.0).into_bytes()
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.put_u8(b'-');
+        buf.put_slice(self.0.as_bytes());
+        buf.put_slice(CRLF);
     }
 }
 
@@ -1,8 +1,10 @@
 use std::ops::Deref;
 
+use bytes::{BufMut, BytesMut};
+
 use crate::resp::extract_simple_frame_data;
 
-use super::{RespDecode, RespEncode, RespError, CRLF_LEN};
+use super::{RespDecode, RespEncode, RespError, CRLF, CRLF_LEN};
 
 // Simple strings are encoded as a plus (+) character, followed by a string. The string mustn't contain a CR (\r) or LF (\n) character and is terminated by CRLF (i.e., \r\n).
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
@@ -15,8 +17,10 @@ impl SimpleString {
 }
 
 impl RespEncode for SimpleString {
-    fn encode(self) -> Vec<u8> {
-        format!("+{}\r\n", self.0).into_bytes()
+    fn encode_to(&self, buf: &mut BytesMut) {
+        buf.put_u8(b'+');
+        buf.put_slice(self.0.as_bytes());
+        buf.put_slice(CRLF);
     }
 }
 
@@ -24,8 +28,6 @@ impl RespDecode for SimpleString {
     const PREFIX: &'static str = "+";
     fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
         let end = extract_simple_frame_data(buf, Self::PREFIX)?;
-        println!("end: {}", end);
-        println!("end + CRLF_LEN): {}", end + CRLF_LEN);
 
         let data = buf.split_to(end + CRLF_LEN);
         let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
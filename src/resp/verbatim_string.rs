@@ -0,0 +1,93 @@
+use bytes::{BufMut, BytesMut};
+
+use super::{
+    bulk_expect_length, extract_bulk_data, write_header, RespDecode, RespEncode, RespError, CRLF,
+};
+
+// https://redis.io/docs/latest/develop/reference/protocol-spec/#verbatim-strings
+// =<length>\r\n<encoding>:<data>\r\n
+// `encoding` is exactly three bytes, e.g. `txt` or `mkd`, and is part of the length.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct VerbatimString {
+    pub(crate) encoding: [u8; 3],
+    pub(crate) data: Vec<u8>,
+}
+
+impl VerbatimString {
+    pub fn new(encoding: [u8; 3], data: impl Into<Vec<u8>>) -> Self {
+        VerbatimString {
+            encoding,
+            data: data.into(),
+        }
+    }
+
+    // Plain text, as used by INFO and the like.
+    pub fn txt(data: impl Into<Vec<u8>>) -> Self {
+        Self::new(*b"txt", data)
+    }
+
+    pub fn encoding(&self) -> &[u8; 3] {
+        &self.encoding
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl RespEncode for VerbatimString {
+    fn encode_to(&self, buf: &mut BytesMut) {
+        let len = self.encoding.len() + 1 + self.data.len();
+        write_header(buf, b'=', len);
+        buf.put_slice(&self.encoding);
+        buf.put_u8(b':');
+        buf.put_slice(&self.data);
+        buf.put_slice(CRLF);
+    }
+}
+
+impl RespDecode for VerbatimString {
+    const PREFIX: &'static str = "=";
+    fn decode(buf: &mut bytes::BytesMut) -> Result<Self, RespError> {
+        let data = extract_bulk_data(buf, Self::PREFIX)?;
+        if data.len() < 4 || data[3] != b':' {
+            return Err(RespError::InvalidFrame(format!(
+                "verbatim string must start with a 3 bytes encoding: {}",
+                String::from_utf8_lossy(&data)
+            )));
+        }
+        let encoding = [data[0], data[1], data[2]];
+        Ok(VerbatimString::new(encoding, data[4..].to_vec()))
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        bulk_expect_length(buf, Self::PREFIX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use crate::resp::RespFrame;
+
+    use super::*;
+
+    #[test]
+    fn test_verbatim_string() -> anyhow::Result<()> {
+        let frame: RespFrame = VerbatimString::txt("Some string").into();
+        let encoded = frame.encode();
+        assert_eq!(encoded, b"=15\r\ntxt:Some string\r\n");
+
+        let mut buf = BytesMut::from(&encoded[..]);
+        assert_eq!(RespFrame::expect_length(&buf)?, encoded.len());
+        let s = VerbatimString::decode(&mut buf)?;
+        assert_eq!(s.encoding(), b"txt");
+        assert_eq!(s.data(), b"Some string");
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from(&b"=3\r\ntxt\r\n"[..]);
+        assert!(VerbatimString::decode(&mut buf).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicU8, AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    time::Instant,
+};
+
+use bytes::Bytes;
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    Notify,
+};
+
+use crate::{pubsub::ClientOutputBufferLimit, resp::RespFrame};
+
+// Per-connection state owned by the connection task.
+#[derive(Debug)]
+pub struct Session {
+    pub(crate) handle: ClientHandle,
+    pub(crate) channels: BTreeSet<Bytes>,
+    pub(crate) patterns: BTreeSet<Bytes>,
+    pub(crate) shard_channels: BTreeSet<Bytes>,
+    replies: Vec<RespFrame>,
+    // CLIENT CACHING YES|NO, only valid for the next command
+    caching: Option<bool>,
+    name: Option<String>,
+    // commands other than AUTH and HELLO are refused until set
+    authenticated: bool,
+    // the ACL user the commands run as
+    user: String,
+}
+
+// A cheap, cloneable reference to a connection which other connections use
+// to deliver out-of-band frames such as pub/sub messages.
+#[derive(Debug, Clone)]
+pub struct ClientHandle(Arc<ClientHandleInner>);
+
+#[derive(Debug)]
+pub struct ClientHandleInner {
+    id: u64,
+    // RESP protocol version negotiated with HELLO
+    protocol: AtomicU8,
+    tx: UnboundedSender<QueuedFrame>,
+    // bytes queued for this connection but not yet written to the socket
+    pending_bytes: AtomicUsize,
+    soft_limit_since: Mutex<Option<Instant>>,
+    close_reason: OnceLock<CloseReason>,
+    close_notify: Notify,
+}
+
+// A frame waiting in a connection's output queue, with its encoded size.
+#[derive(Debug)]
+pub struct QueuedFrame {
+    pub frame: RespFrame,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseReason {
+    OutputBufferLimit,
+}
+
+impl Session {
+    pub fn new(id: u64) -> (Self, UnboundedReceiver<QueuedFrame>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let session = Self {
+            handle: ClientHandle(Arc::new(ClientHandleInner {
+                id,
+                protocol: AtomicU8::new(2),
+                tx,
+                pending_bytes: AtomicUsize::new(0),
+                soft_limit_since: Mutex::new(None),
+                close_reason: OnceLock::new(),
+                close_notify: Notify::new(),
+            })),
+            channels: BTreeSet::new(),
+            patterns: BTreeSet::new(),
+            shard_channels: BTreeSet::new(),
+            replies: Vec::new(),
+            caching: None,
+            name: None,
+            authenticated: false,
+            user: "default".to_string(),
+        };
+        (session, rx)
+    }
+
+    pub fn id(&self) -> u64 {
+        self.handle.id()
+    }
+
+    pub fn handle(&self) -> &ClientHandle {
+        &self.handle
+    }
+
+    // Whether the connection is subscribed to any channel, pattern or shard channel.
+    pub fn is_subscribed(&self) -> bool {
+        !(self.channels.is_empty() && self.patterns.is_empty() && self.shard_channels.is_empty())
+    }
+
+    // Queue an extra reply which is written before the frame returned by the
+    // command, for commands such as SSUBSCRIBE that reply once per argument.
+    pub fn reply(&mut self, frame: impl Into<RespFrame>) {
+        self.replies.push(frame.into());
+    }
+
+    pub fn take_replies(&mut self) -> Vec<RespFrame> {
+        std::mem::take(&mut self.replies)
+    }
+
+    pub fn caching(&self) -> Option<bool> {
+        self.caching
+    }
+
+    pub fn set_caching(&mut self, caching: Option<bool>) {
+        self.caching = caching;
+    }
+
+    pub fn protocol(&self) -> u8 {
+        self.handle.protocol()
+    }
+
+    pub fn set_protocol(&mut self, protocol: u8) {
+        self.handle.0.protocol.store(protocol, Ordering::Relaxed);
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated
+    }
+
+    pub fn set_authenticated(&mut self, authenticated: bool) {
+        self.authenticated = authenticated;
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    pub fn set_user(&mut self, user: impl Into<String>) {
+        self.user = user.into();
+    }
+}
+
+impl ClientHandle {
+    pub fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    pub fn protocol(&self) -> u8 {
+        self.0.protocol.load(Ordering::Relaxed)
+    }
+
+    // Queue a frame of `size` encoded bytes for the connection. Returns false
+    // if the connection is gone, or if the frame pushed the queue over the
+    // output buffer limit, in which case the connection is closed.
+    pub fn send(&self, frame: RespFrame, size: usize, limit: &ClientOutputBufferLimit) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+
+        let pending = self.0.pending_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        let exceeded = {
+            let mut since = self.0.soft_limit_since.lock().unwrap();
+            limit.is_exceeded(pending, &mut since, Instant::now())
+        };
+        if exceeded {
+            self.close(CloseReason::OutputBufferLimit);
+            return false;
+        }
+
+        self.0.tx.send(QueuedFrame { frame, size }).is_ok()
+    }
+
+    // Called by the connection once a queued frame has been written out.
+    pub fn sent(&self, size: usize) {
+        self.0.pending_bytes.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    pub fn pending_bytes(&self) -> usize {
+        self.0.pending_bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn close(&self, reason: CloseReason) {
+        if self.0.close_reason.set(reason).is_ok() {
+            self.0.close_notify.notify_one();
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.close_reason().is_some()
+    }
+
+    pub fn close_reason(&self) -> Option<CloseReason> {
+        self.0.close_reason.get().copied()
+    }
+
+    // Resolves once the connection has been asked to close.
+    pub async fn closed(&self) {
+        if !self.is_closed() {
+            self.0.close_notify.notified().await;
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::Mutex;
+
+use tokio_util::sync::CancellationToken;
+
+// SHUTDOWN [NOSAVE|SAVE] [NOW] [FORCE]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownFlags {
+    // `None` saves only if save points are configured
+    pub save: Option<bool>,
+    // don't wait for lagging replicas
+    pub now: bool,
+    // exit even if the final save fails
+    pub force: bool,
+}
+
+// Server wide shutdown, requested by SHUTDOWN or a signal. Listeners stop
+// accepting and connections close once their current command is done.
+#[derive(Debug, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    flags: Mutex<Option<ShutdownFlags>>,
+}
+
+impl Shutdown {
+    // Returns false if a shutdown was already requested, the flags of the
+    // first request are kept.
+    pub fn request(&self, flags: ShutdownFlags) -> bool {
+        let mut requested = self.flags.lock().unwrap();
+        if requested.is_some() {
+            return false;
+        }
+        *requested = Some(flags);
+        self.token.cancel();
+        true
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    pub fn flags(&self) -> Option<ShutdownFlags> {
+        *self.flags.lock().unwrap()
+    }
+
+    pub async fn requested(&self) {
+        self.token.cancelled().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_request() {
+        let shutdown = Shutdown::default();
+        assert!(!shutdown.is_requested());
+        assert_eq!(shutdown.flags(), None);
+
+        let flags = ShutdownFlags {
+            save: Some(false),
+            ..Default::default()
+        };
+        assert!(shutdown.request(flags));
+        assert!(!shutdown.request(ShutdownFlags::default()));
+        shutdown.requested().await;
+        assert!(shutdown.is_requested());
+        assert_eq!(shutdown.flags(), Some(flags));
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Server counters reported by INFO.
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub connected_clients: AtomicU64,
+    pub total_connections_received: AtomicU64,
+    pub rejected_connections: AtomicU64,
+    pub total_commands_processed: AtomicU64,
+    pub client_output_buffer_limit_disconnections: AtomicU64,
+}
+
+impl Stats {
+    pub fn incr(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn decr(counter: &AtomicU64) {
+        counter.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn get(counter: &AtomicU64) -> u64 {
+        counter.load(Ordering::Relaxed)
+    }
+
+    // CONFIG RESETSTAT, gauges like connected_clients are kept.
+    pub fn reset(&self) {
+        for counter in [
+            &self.total_connections_received,
+            &self.rejected_connections,
+            &self.total_commands_processed,
+            &self.client_output_buffer_limit_disconnections,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
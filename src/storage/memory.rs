@@ -1,16 +1,24 @@
 use std::{ops::Deref, sync::Arc};
 
+use bytes::Bytes;
 use dashmap::DashMap;
 
-use crate::resp::RespFrame;
+use crate::resp::{BulkString, RespFrame};
+
+// Keys and values decoded from a request are slices of the connection's read
+// buffer, storing one keeps that whole buffer allocated. Keys and small values
+// are copied so that a few of them can't pin a large pipelined read; larger
+// values are stored as-is, they take up most of the buffer they keep anyway.
+const COPY_BELOW: usize = 16 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct InMemStore(Arc<InMemStoreInner>);
 
+// Keys and hash fields are binary safe, like values.
 #[derive(Debug)]
 pub struct InMemStoreInner {
-    pub(crate) map: DashMap<String, RespFrame>,
-    pub(crate) hmap: DashMap<String, DashMap<String, RespFrame>>,
+    pub(crate) map: DashMap<Bytes, RespFrame>,
+    pub(crate) hmap: DashMap<Bytes, DashMap<Bytes, RespFrame>>,
 }
 
 impl Deref for InMemStore {
@@ -40,29 +48,43 @@ impl InMemStore {
         Self::default()
     }
 
-    pub fn get(&self, key: &str) -> Option<RespFrame> {
+    pub fn get(&self, key: &[u8]) -> Option<RespFrame> {
         self.map.get(key).map(|v| v.value().clone())
     }
 
-    pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+    // Returns the previous value, if the key already existed.
+    pub fn set(&self, key: Bytes, value: RespFrame) -> Option<RespFrame> {
+        self.map.insert(detach(&key), detach_value(value))
     }
 
-    pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
+    pub fn hget(&self, key: &[u8], field: &[u8]) -> Option<RespFrame> {
         self.hmap
             .get(key)
             .and_then(|v| v.get(field).map(|v| v.value().clone()))
     }
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_default();
-        hmap.insert(field, value);
+    pub fn hset(&self, key: Bytes, field: Bytes, value: RespFrame) {
+        let hmap = self.hmap.entry(detach(&key)).or_default();
+        hmap.insert(detach(&field), detach_value(value));
     }
 
-    pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
+    pub fn hgetall(&self, key: &[u8]) -> Option<DashMap<Bytes, RespFrame>> {
         self.hmap.get(key).map(|v| v.clone())
     }
 }
 
+fn detach(bytes: &Bytes) -> Bytes {
+    Bytes::copy_from_slice(bytes)
+}
+
+fn detach_value(value: RespFrame) -> RespFrame {
+    match value {
+        RespFrame::BulkString(BulkString(bytes)) if bytes.len() < COPY_BELOW => {
+            BulkString(detach(&bytes)).into()
+        }
+        value => value,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,9 +92,9 @@ mod tests {
     #[test]
     fn test_set_get() {
         let store = InMemStore::new();
-        store.set("foo".to_string(), RespFrame::SimpleString("Hello".into()));
+        store.set(Bytes::from("foo"), RespFrame::SimpleString("Hello".into()));
         assert_eq!(
-            store.get("foo"),
+            store.get(b"foo"),
             Some(RespFrame::SimpleString("Hello".into()))
         );
     }
@@ -81,11 +103,11 @@ mod tests {
     fn test_hset_hget() {
         let store = InMemStore::new();
         store.hset(
-            "price".to_string(),
-            "Mac".to_string(),
+            Bytes::from("price"),
+            Bytes::from("Mac"),
             RespFrame::Integer(3000),
         );
-        let value = store.hget("price", "Mac");
+        let value = store.hget(b"price", b"Mac");
         assert_eq!(value, Some(RespFrame::Integer(3000)));
     }
 
@@ -93,35 +115,73 @@ mod tests {
     fn test_hgetall() {
         let store = InMemStore::new();
         store.hset(
-            "blog".to_string(),
-            "author".to_string(),
+            Bytes::from("blog"),
+            Bytes::from("author"),
             RespFrame::SimpleString("Alex".into()),
         );
         store.hset(
-            "blog".to_string(),
-            "content".to_string(),
+            Bytes::from("blog"),
+            Bytes::from("content"),
             RespFrame::SimpleString("this is a blog".into()),
         );
         store.hset(
-            "blog".to_string(),
-            "create_time".to_string(),
+            Bytes::from("blog"),
+            Bytes::from("create_time"),
             RespFrame::SimpleString("2024-08-17".into()),
         );
 
-        let blog_data = store.hgetall("blog").unwrap();
+        let blog_data = store.hgetall(b"blog").unwrap();
 
         assert_eq!(blog_data.len(), 3);
         assert_eq!(
-            blog_data.get("author").unwrap().value(),
+            blog_data.get(&b"author"[..]).unwrap().value(),
             &RespFrame::SimpleString("Alex".into())
         );
         assert_eq!(
-            blog_data.get("content").unwrap().value(),
+            blog_data.get(&b"content"[..]).unwrap().value(),
             &RespFrame::SimpleString("this is a blog".into())
         );
         assert_eq!(
-            blog_data.get("create_time").unwrap().value(),
+            blog_data.get(&b"create_time"[..]).unwrap().value(),
             &RespFrame::SimpleString("2024-08-17".into())
         );
     }
+
+    #[test]
+    fn test_set_detaches_small_values() {
+        let store = InMemStore::new();
+        let buf = Bytes::from(vec![b'x'; 2 * COPY_BELOW]);
+        let key = buf.slice(..3);
+        let small = buf.slice(3..10);
+        let large = buf.slice(..COPY_BELOW);
+
+        store.set(key.clone(), BulkString(small.clone()).into());
+        store.set(Bytes::from("large"), BulkString(large.clone()).into());
+        let stored_key = store.map.iter().find(|e| e.key() == &key).unwrap();
+        assert_ne!(stored_key.key().as_ptr(), key.as_ptr());
+        match (store.get(&key), store.get(b"large")) {
+            (Some(RespFrame::BulkString(s)), Some(RespFrame::BulkString(l))) => {
+                assert_eq!(s.0, small);
+                assert_ne!(s.as_ptr(), small.as_ptr());
+                assert_eq!(l.as_ptr(), large.as_ptr());
+            }
+            frames => panic!("unexpected values: {:?}", frames),
+        }
+    }
+
+    #[test]
+    fn test_binary_keys() {
+        let store = InMemStore::new();
+        let key = Bytes::from_static(b"\x00\xff\xfe");
+        store.set(key.clone(), RespFrame::Integer(1));
+        store.hset(
+            key.clone(),
+            Bytes::from_static(b"\xc0"),
+            RespFrame::Integer(2),
+        );
+
+        assert_eq!(store.get(&key), Some(RespFrame::Integer(1)));
+        assert_eq!(store.get(b"\x00\xff"), None);
+        assert_eq!(store.hget(&key, b"\xc0"), Some(RespFrame::Integer(2)));
+    }
 }
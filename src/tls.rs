@@ -0,0 +1,169 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use rustls::{
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    server::{VerifierBuilderError, WebPkiClientVerifier},
+    RootCertStore, ServerConfig,
+};
+use thiserror::Error;
+use tokio_rustls::TlsAcceptor;
+
+use crate::config::{Config, TlsAuthClients};
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("{0} must be specified")]
+    Missing(&'static str),
+    #[error("can't load '{}': {source}", path.display())]
+    Pem {
+        path: PathBuf,
+        source: rustls::pki_types::pem::Error,
+    },
+    #[error("no certificate found in '{}'", .0.display())]
+    NoCertificate(PathBuf),
+    #[error(transparent)]
+    Rustls(#[from] rustls::Error),
+    #[error(transparent)]
+    Verifier(#[from] VerifierBuilderError),
+}
+
+// The acceptor of the TLS port. It is rebuilt from the certificate files
+// when they are set with CONFIG SET, connections already established keep
+// the certificates they were accepted with.
+#[derive(Default)]
+pub struct Tls {
+    acceptor: RwLock<Option<TlsAcceptor>>,
+}
+
+impl fmt::Debug for Tls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tls")
+            .field("enabled", &self.acceptor().is_some())
+            .finish()
+    }
+}
+
+impl Tls {
+    pub fn acceptor(&self) -> Option<TlsAcceptor> {
+        self.acceptor.read().unwrap().clone()
+    }
+
+    // Read the certificate files again. The running acceptor is kept if they
+    // can't be loaded.
+    pub fn reload(&self, config: &Config) -> Result<(), TlsError> {
+        let acceptor = match config.tls_port {
+            0 => None,
+            _ => Some(TlsAcceptor::from(Arc::new(server_config(config)?))),
+        };
+        *self.acceptor.write().unwrap() = acceptor;
+        Ok(())
+    }
+}
+
+fn server_config(config: &Config) -> Result<ServerConfig, TlsError> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let cert_file = config
+        .tls_cert_file
+        .as_deref()
+        .ok_or(TlsError::Missing("tls-cert-file"))?;
+    let key_file = config
+        .tls_key_file
+        .as_deref()
+        .ok_or(TlsError::Missing("tls-key-file"))?;
+    let certs = load_certs(cert_file)?;
+    let key = PrivateKeyDer::from_pem_file(key_file).map_err(|source| TlsError::Pem {
+        path: key_file.to_path_buf(),
+        source,
+    })?;
+
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    let builder = match config.tls_auth_clients {
+        TlsAuthClients::No => builder.with_no_client_auth(),
+        auth => {
+            let ca_file = config
+                .tls_ca_cert_file
+                .as_deref()
+                .ok_or(TlsError::Missing("tls-ca-cert-file"))?;
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(ca_file)? {
+                roots.add(cert)?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+            let verifier = match auth {
+                TlsAuthClients::Optional => verifier.allow_unauthenticated(),
+                _ => verifier,
+            };
+            builder.with_client_cert_verifier(verifier.build()?)
+        }
+    };
+    Ok(builder.with_single_cert(certs, key)?)
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let pem_error = |source| TlsError::Pem {
+        path: path.to_path_buf(),
+        source,
+    };
+    let certs = CertificateDer::pem_file_iter(path)
+        .map_err(pem_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(pem_error)?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificate(path.to_path_buf()));
+    }
+    Ok(certs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_reload() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("simple-redis-tls-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+        fs::write(dir.join("redis.crt"), certified.cert.pem())?;
+        fs::write(dir.join("redis.key"), certified.key_pair.serialize_pem())?;
+        fs::write(dir.join("empty.crt"), "")?;
+
+        let tls = Tls::default();
+        let mut config = Config::default();
+        tls.reload(&config)?;
+        assert!(tls.acceptor().is_none());
+
+        config.tls_port = 6390;
+        let err = tls.reload(&config).unwrap_err();
+        assert_eq!(err.to_string(), "tls-cert-file must be specified");
+
+        config.tls_cert_file = Some(dir.join("redis.crt"));
+        config.tls_key_file = Some(dir.join("redis.key"));
+        let err = tls.reload(&config).unwrap_err();
+        assert_eq!(err.to_string(), "tls-ca-cert-file must be specified");
+
+        config.tls_auth_clients = TlsAuthClients::No;
+        tls.reload(&config)?;
+        assert!(tls.acceptor().is_some());
+
+        // a failed reload keeps the running acceptor
+        config.tls_cert_file = Some(dir.join("empty.crt"));
+        let err = tls.reload(&config);
+        config.tls_ca_cert_file = Some(dir.join("redis.crt"));
+        config.tls_cert_file = Some(dir.join("redis.crt"));
+        config.tls_auth_clients = TlsAuthClients::Optional;
+        let ret = tls.reload(&config);
+        fs::remove_dir_all(&dir)?;
+
+        assert!(matches!(err, Err(TlsError::NoCertificate(_))));
+        assert!(tls.acceptor().is_some());
+        ret?;
+        Ok(())
+    }
+}
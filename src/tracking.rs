@@ -0,0 +1,169 @@
+use std::collections::HashSet;
+
+use bytes::Bytes;
+use dashmap::DashMap;
+
+use crate::{
+    backend::Backend,
+    resp::{BulkString, RespArray, RespEncode, RespFrame, RespPush},
+    session::{ClientHandle, Session},
+};
+
+pub const INVALIDATE_CHANNEL: &str = "__redis__:invalidate";
+
+// Client side caching support, see
+// https://redis.io/docs/latest/develop/reference/client-side-caching/
+#[derive(Debug, Default)]
+pub struct Tracking {
+    // key -> clients which read it since it was last invalidated (default mode)
+    keys: DashMap<Bytes, HashSet<u64>>,
+    // clients with tracking enabled
+    clients: DashMap<u64, TrackingOptions>,
+}
+
+// CLIENT TRACKING ON [REDIRECT id] [PREFIX prefix ...] [BCAST] [OPTIN] [OPTOUT] [NOLOOP]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackingOptions {
+    pub redirect: Option<u64>,
+    pub bcast: bool,
+    pub prefixes: Vec<Bytes>,
+    pub optin: bool,
+    pub optout: bool,
+    pub noloop: bool,
+}
+
+impl Tracking {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable(&self, client_id: u64, options: TrackingOptions) {
+        self.clients.insert(client_id, options);
+    }
+
+    // Keys remembered for the client are dropped lazily on invalidation.
+    pub fn disable(&self, client_id: u64) {
+        self.clients.remove(&client_id);
+    }
+
+    pub fn options(&self, client_id: u64) -> Option<TrackingOptions> {
+        self.clients.get(&client_id).map(|c| c.value().clone())
+    }
+
+    // Remember that the client read the key, unless it is in BCAST mode or
+    // opted out of caching for this command.
+    pub fn track_read(&self, session: &Session, key: &Bytes) {
+        let Some(options) = self.clients.get(&session.id()) else {
+            return;
+        };
+        let caching = session.caching();
+        let track = if options.bcast {
+            false
+        } else if options.optin {
+            caching == Some(true)
+        } else if options.optout {
+            caching != Some(false)
+        } else {
+            true
+        };
+
+        if track {
+            self.keys
+                .entry(key.clone())
+                .or_default()
+                .insert(session.id());
+        }
+    }
+
+    // Send invalidation messages for the key to every client which read it
+    // or is broadcasting a matching prefix. `writer` is the client modifying
+    // the key, which is skipped if it enabled NOLOOP.
+    pub fn invalidate_key(&self, backend: &Backend, key: &[u8], writer: u64) {
+        let mut targets: Vec<u64> = self
+            .keys
+            .remove(key)
+            .map(|(_, clients)| clients.into_iter().collect())
+            .unwrap_or_default();
+
+        for entry in self.clients.iter() {
+            let options = entry.value();
+            if options.bcast
+                && (options.prefixes.is_empty()
+                    || options.prefixes.iter().any(|p| key.starts_with(p)))
+            {
+                targets.push(*entry.key());
+            }
+        }
+
+        for client_id in targets {
+            let Some(options) = self.options(client_id) else {
+                continue;
+            };
+            if options.noloop && client_id == writer {
+                continue;
+            }
+            send_invalidation(backend, client_id, &options, key);
+        }
+    }
+}
+
+fn send_invalidation(backend: &Backend, client_id: u64, options: &TrackingOptions, key: &[u8]) {
+    let limit = backend.pubsub.output_buffer_limit();
+    let send = |handle: &ClientHandle, frame: RespFrame| {
+        let size = frame.encode().len();
+        handle.send(frame, size, &limit);
+    };
+
+    let Some(client) = backend.client(client_id) else {
+        return;
+    };
+    let target = match options.redirect {
+        Some(redirect) => match backend.client(redirect) {
+            Some(target) => target,
+            None => {
+                if client.protocol() >= 3 {
+                    send(&client, redir_broken_frame());
+                }
+                return;
+            }
+        },
+        None => client,
+    };
+
+    if target.protocol() >= 3 {
+        send(&target, invalidation_frame(key));
+    } else if options.redirect.is_some()
+        && backend
+            .pubsub
+            .channels
+            .contains(INVALIDATE_CHANNEL.as_bytes(), target.id())
+    {
+        // RESP2 connections can only receive invalidations as messages on
+        // `__redis__:invalidate`, through REDIRECT
+        send(&target, invalidation_message_frame(key));
+    }
+}
+
+// `[invalidate, [key]]`
+pub fn invalidation_frame(key: &[u8]) -> RespFrame {
+    RespPush::new(vec![
+        BulkString::from("invalidate").into(),
+        RespArray::new(vec![BulkString::from(key).into()]).into(),
+    ])
+    .into()
+}
+
+// `[message, __redis__:invalidate, [key]]`
+pub fn invalidation_message_frame(key: &[u8]) -> RespFrame {
+    RespPush::new(vec![
+        BulkString::from("message").into(),
+        BulkString::from(INVALIDATE_CHANNEL).into(),
+        RespArray::new(vec![BulkString::from(key).into()]).into(),
+    ])
+    .into()
+}
+
+// Sent to a RESP3 client whose redirect target went away.
+fn redir_broken_frame() -> RespFrame {
+    RespPush::new(vec![BulkString::from("tracking-redir-broken").into()]).into()
+}
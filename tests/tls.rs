@@ -0,0 +1,250 @@
+use std::{
+    fs,
+    net::TcpListener as StdTcpListener,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa,
+    KeyPair,
+};
+use rustls::{
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer, ServerName},
+    ClientConfig, RootCertStore,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_rustls::{client::TlsStream, TlsConnector};
+
+struct Ca {
+    cert: Certificate,
+    key: KeyPair,
+}
+
+impl Ca {
+    fn new(name: &str) -> Result<Self> {
+        let mut params = CertificateParams::new(vec![])?;
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.distinguished_name.push(DnType::CommonName, name);
+        let key = KeyPair::generate()?;
+        let cert = params.self_signed(&key)?;
+        Ok(Self { cert, key })
+    }
+
+    // A certificate for localhost signed by this CA, as PEM cert and key.
+    fn issue(&self, usage: ExtendedKeyUsagePurpose) -> Result<(String, String)> {
+        let mut params = CertificateParams::new(vec!["localhost".to_string()])?;
+        params.extended_key_usages = vec![usage];
+        let key = KeyPair::generate()?;
+        let cert = params.signed_by(&key, &self.cert, &self.key)?;
+        Ok((cert.pem(), key.serialize_pem()))
+    }
+}
+
+// The server binary running on a TLS port, killed when dropped.
+struct Server {
+    child: Child,
+    port: u16,
+    dir: PathBuf,
+}
+
+impl Server {
+    fn start(name: &str, ca: &Ca, auth_clients: &str) -> Result<Self> {
+        let dir =
+            std::env::temp_dir().join(format!("simple-redis-tls-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let (cert, key) = ca.issue(ExtendedKeyUsagePurpose::ServerAuth)?;
+        fs::write(dir.join("redis.crt"), cert)?;
+        fs::write(dir.join("redis.key"), key)?;
+        fs::write(dir.join("ca.crt"), ca.cert.pem())?;
+
+        let port = StdTcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let child = Command::new(env!("CARGO_BIN_EXE_simple-redis"))
+            .args([
+                "--port",
+                "0",
+                "--bind",
+                "127.0.0.1",
+                "--loglevel",
+                "warning",
+            ])
+            .args(["--tls-port", &port.to_string()])
+            .args([
+                "--tls-cert-file",
+                "redis.crt",
+                "--tls-key-file",
+                "redis.key",
+            ])
+            .args(["--tls-ca-cert-file", "ca.crt"])
+            .args(["--tls-auth-clients", auth_clients])
+            .args(["--dir", &dir.display().to_string()])
+            .stdout(Stdio::null())
+            .spawn()?;
+        let server = Self { child, port, dir };
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while std::net::TcpStream::connect(("127.0.0.1", port)).is_err() {
+            anyhow::ensure!(Instant::now() < deadline, "server didn't start");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        Ok(server)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    async fn connect(
+        &self,
+        ca: &Ca,
+        client_cert: Option<(&str, &str)>,
+    ) -> Result<TlsStream<TcpStream>> {
+        let mut roots = RootCertStore::empty();
+        roots.add(ca.cert.der().clone())?;
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots);
+        let config = match client_cert {
+            Some((cert, key)) => builder.with_client_auth_cert(
+                vec![CertificateDer::from_pem_slice(cert.as_bytes())?],
+                PrivateKeyDer::from_pem_slice(key.as_bytes())?,
+            )?,
+            None => builder.with_no_client_auth(),
+        };
+
+        let stream = TcpStream::connect(("127.0.0.1", self.port)).await?;
+        let connector = TlsConnector::from(Arc::new(config));
+        Ok(connector
+            .connect(ServerName::try_from("localhost")?, stream)
+            .await?)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+// Send an inline command and read its one line reply.
+async fn request(stream: &mut TlsStream<TcpStream>, command: &str) -> Result<String> {
+    stream
+        .write_all(format!("{}\r\n", command).as_bytes())
+        .await?;
+    let mut reply = vec![];
+    while !reply.ends_with(b"\r\n") {
+        let mut buf = [0; 1024];
+        let n = stream.read(&mut buf).await?;
+        anyhow::ensure!(n > 0, "connection closed");
+        reply.extend_from_slice(&buf[..n]);
+    }
+    Ok(String::from_utf8(reply)?)
+}
+
+fn write_cert(path: &Path, (cert, key): &(String, String)) -> Result<()> {
+    fs::write(path.with_extension("crt"), cert)?;
+    fs::write(path.with_extension("key"), key)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tls_round_trip() -> Result<()> {
+    let ca = Ca::new("round trip ca")?;
+    let server = Server::start("round-trip", &ca, "no")?;
+
+    let mut client = server.connect(&ca, None).await?;
+    assert_eq!(request(&mut client, "SET greeting hello").await?, "+OK\r\n");
+    assert_eq!(
+        request(&mut client, "GET greeting").await?,
+        "$5\r\nhello\r\n"
+    );
+
+    // a client that doesn't trust the server's CA can't connect
+    let other = Ca::new("other ca")?;
+    assert!(server.connect(&other, None).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tls_auth_clients() -> Result<()> {
+    let ca = Ca::new("auth ca")?;
+    let client_cert = ca.issue(ExtendedKeyUsagePurpose::ClientAuth)?;
+    let other_cert = Ca::new("other ca")?.issue(ExtendedKeyUsagePurpose::ClientAuth)?;
+    let client = Some((client_cert.0.as_str(), client_cert.1.as_str()));
+    let other = Some((other_cert.0.as_str(), other_cert.1.as_str()));
+
+    let server = Server::start("auth-yes", &ca, "yes")?;
+    let mut stream = server.connect(&ca, client).await?;
+    assert_eq!(request(&mut stream, "SET k v").await?, "+OK\r\n");
+    // with TLS 1.3 the client only learns it was rejected on its first read
+    for cert in [None, other] {
+        let rejected = match server.connect(&ca, cert).await {
+            Ok(mut stream) => request(&mut stream, "GET k").await.is_err(),
+            Err(_) => true,
+        };
+        assert!(rejected);
+    }
+
+    let server = Server::start("auth-optional", &ca, "optional")?;
+    let mut stream = server.connect(&ca, None).await?;
+    assert_eq!(request(&mut stream, "SET k v").await?, "+OK\r\n");
+    let mut stream = server.connect(&ca, client).await?;
+    assert_eq!(request(&mut stream, "GET k").await?, "$1\r\nv\r\n");
+    let rejected = match server.connect(&ca, other).await {
+        Ok(mut stream) => request(&mut stream, "GET k").await.is_err(),
+        Err(_) => true,
+    };
+    assert!(rejected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tls_reload_certificates() -> Result<()> {
+    let ca = Ca::new("first ca")?;
+    let server = Server::start("reload", &ca, "no")?;
+    let mut stream = server.connect(&ca, None).await?;
+
+    let next_ca = Ca::new("next ca")?;
+    write_cert(
+        &server.path("next.crt"),
+        &next_ca.issue(ExtendedKeyUsagePurpose::ServerAuth)?,
+    )?;
+    assert!(server.connect(&next_ca, None).await.is_err());
+
+    let reply = request(&mut stream, "CONFIG SET tls-cert-file missing.crt").await?;
+    assert!(reply.starts_with(
+        "-ERR CONFIG SET failed (possibly related to argument 'tls-cert-file') - Unable to update TLS configuration"
+    ));
+    assert!(server.connect(&ca, None).await.is_ok());
+
+    let reply = request(
+        &mut stream,
+        "CONFIG SET tls-cert-file next.crt tls-key-file next.key",
+    )
+    .await?;
+    assert_eq!(reply, "+OK\r\n");
+    let mut next = server.connect(&next_ca, None).await?;
+    assert_eq!(request(&mut next, "SET k v").await?, "+OK\r\n");
+    assert!(server.connect(&ca, None).await.is_err());
+    // established connections are not affected
+    assert_eq!(request(&mut stream, "GET k").await?, "$1\r\nv\r\n");
+
+    // setting the same file again picks up its new content
+    write_cert(
+        &server.path("next.crt"),
+        &ca.issue(ExtendedKeyUsagePurpose::ServerAuth)?,
+    )?;
+    let reply = request(&mut stream, "CONFIG SET tls-cert-file next.crt").await?;
+    assert_eq!(reply, "+OK\r\n");
+    assert!(server.connect(&ca, None).await.is_ok());
+    Ok(())
+}